@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MIT
+pragma solidity 0.8.24;
+
+/// @title Batch taker
+/// @notice Multicall executor the simulator puts on the fork with `anvil_setCode`. Its
+/// runtime is embedded as BatchTaker.bin-runtime; regenerate it after any change with
+///
+///     solc-0.8.24 --optimize --optimize-runs 200 --evm-version shanghai --metadata-hash none \
+///         --bin-runtime contracts/BatchTaker.sol | tail -1 > contracts/BatchTaker.bin-runtime
+///
+/// There is no access control, it only ever runs on a fork.
+contract BatchTaker {
+    /// @dev IOrderMixin.Order, the `Address` fields as uint256
+    struct Order {
+        uint256 salt;
+        uint256 maker;
+        uint256 receiver;
+        uint256 makerAsset;
+        uint256 takerAsset;
+        uint256 makingAmount;
+        uint256 takingAmount;
+        uint256 makerTraits;
+    }
+
+    receive() external payable {}
+
+    /// @notice Run `calls`, packed as
+    /// `[address target (20)][uint8 allowFailure (1)][uint32 length (4)][padding (7)][uint256 value (32)][data (length)]`
+    /// @return results One `[uint256 success][uint256 length][returndata (length)]` per call
+    function execute(bytes calldata calls) external payable returns (bytes memory results) {
+        return _run(calls);
+    }
+
+    /// @notice ITakerInteraction hook: the router calls it mid-fill, `extraData` holds the calls
+    function takerInteraction(
+        Order calldata,
+        bytes calldata,
+        bytes32,
+        address,
+        uint256,
+        uint256,
+        uint256,
+        bytes calldata extraData
+    ) external returns (bytes memory results) {
+        return _run(extraData);
+    }
+
+    function _run(bytes calldata calls) private returns (bytes memory results) {
+        uint256 ptr = 0;
+        while (ptr < calls.length) {
+            uint256 header = uint256(bytes32(calls[ptr:ptr + 32]));
+            address target = address(uint160(header >> 96));
+            bool allowFailure = uint8(header >> 88) != 0;
+            uint256 length = uint32(header >> 56);
+            uint256 value = uint256(bytes32(calls[ptr + 32:ptr + 64]));
+
+            (bool success, bytes memory returnData) = target.call{value: value}(calls[ptr + 64:ptr + 64 + length]);
+            if (!success && !allowFailure) {
+                // Bubble the revert so router errors decode as if the router had been called directly
+                assembly {
+                    revert(add(returnData, 32), mload(returnData))
+                }
+            }
+            results = bytes.concat(results, bytes32(uint256(success ? 1 : 0)), bytes32(returnData.length), returnData);
+            ptr += 64 + length;
+        }
+    }
+}
@@ -1,59 +1,72 @@
-use std::sync::Arc;
 use std::str::FromStr;
+use std::sync::Arc;
 use ethers::{
     providers::{Provider, Http, Middleware},
     signers::{LocalWallet, Signer},
     middleware::SignerMiddleware,
-    types::{Address, U256},
-    contract::Contract,
-    abi::Abi,
-    utils::{Anvil, AnvilInstance, keccak256, hex},
+    types::{Address, Bytes, H256, U256},
+    utils::{Anvil, AnvilInstance},
 };
 use eyre::Result;
 use tracing::{info, error};
-use crate::config::simple_config::Config;
+use crate::config::fork_profile::{ForkProfile, ForkBlock};
+use crate::balance_slot::{find_balance_slot, set_storage_at};
+use crate::bindings::erc20::IERC20;
+use crate::batch_taker::{BATCH_TAKER_ADDRESS, BATCH_TAKER_RUNTIME};
 
 // Type aliases
 pub type SignerClient = SignerMiddleware<Provider<Http>, LocalWallet>;
 
-// Constants
-const RPC_URL: &str = "https://api.zan.top/node/v1/bsc/mainnet/2d661fce966a44139a2d4c61d373851f";
-
-const ERC20_ABI: &str = r#"[
-    {
-        "inputs": [{"internalType": "address", "name": "account", "type": "address"}],
-        "name": "balanceOf",
-        "outputs": [{"internalType": "uint256", "name": "", "type": "uint256"}],
-        "stateMutability": "view",
-        "type": "function"
-    },
-    {
-        "inputs": [
-            {"internalType": "address", "name": "spender", "type": "address"},
-            {"internalType": "uint256", "name": "amount", "type": "uint256"}
-        ],
-        "name": "approve",
-        "outputs": [{"internalType": "bool", "name": "", "type": "bool"}],
-        "stateMutability": "nonpayable",
-        "type": "function"
+pub async fn setup_blockchain(profile: &ForkProfile) -> Result<(AnvilInstance, Arc<SignerClient>)> {
+    // Start Anvil, forking only when the profile has an RPC URL
+    profile.validate()?;
+    let mut anvil = Anvil::new();
+
+    if let Some(rpc_url) = &profile.rpc_url {
+        anvil = anvil.fork(rpc_url.as_str());
+        if let Some(ForkBlock::Number(block)) = profile.fork_block {
+            anvil = anvil.fork_block_number(block);
+        }
+        if let Some(backoff) = profile.fork_retry_backoff {
+            anvil = anvil.args(["--fork-retry-backoff".to_string(), backoff.to_string()]);
+        }
+        if let Some(cups) = profile.compute_units_per_second {
+            anvil = anvil.args(["--compute-units-per-second".to_string(), cups.to_string()]);
+        }
+        info!("Forking chain from {} at block {:?}", rpc_url, profile.fork_block.unwrap_or(ForkBlock::Latest));
+    } else {
+        if let Some(chain_id) = profile.chain_id {
+            anvil = anvil.chain_id(chain_id);
+        }
+        info!("Starting local anvil without fork");
     }
-]"#;
 
-pub async fn setup_blockchain(config: &Config) -> Result<(AnvilInstance, Arc<SignerClient>)> {
-    let fork_block = config.get_fork_block();
+    if let Some(block_time) = profile.block_time {
+        anvil = anvil.block_time(block_time);
+    }
+    if let Some(gas_limit) = profile.gas_limit {
+        anvil = anvil.args(["--gas-limit".to_string(), gas_limit.to_string()]);
+    }
+    if let Some(base_fee) = profile.base_fee {
+        anvil = anvil.args(["--base-fee".to_string(), base_fee.to_string()]);
+    }
 
-    // Start Anvil fork
-    let anvil = Anvil::new()
-        .fork(RPC_URL)
-        .fork_block_number(fork_block)
-        .spawn();
+    let anvil = anvil.spawn();
 
     // Setup provider
     let provider = Provider::<Http>::try_from(anvil.endpoint())?;
 
+    // Use the chain id the node actually reports, not the anvil builder default
+    let chain_id = provider.get_chainid().await?.as_u64();
+    if let Some(expected) = profile.chain_id {
+        if expected != chain_id {
+            return Err(eyre::eyre!("Fork reports chain id {}, profile expects {}", chain_id, expected));
+        }
+    }
+
     // Setup wallet
     let wallet: LocalWallet = anvil.keys()[0].clone().into();
-    let wallet = wallet.with_chain_id(anvil.chain_id());
+    let wallet = wallet.with_chain_id(chain_id);
 
     // Create client
     let client = Arc::new(SignerMiddleware::new(provider, wallet));
@@ -61,33 +74,78 @@ pub async fn setup_blockchain(config: &Config) -> Result<(AnvilInstance, Arc<Sig
     Ok((anvil, client))
 }
 
+/// Another signer on the same fork connection, e.g. a maker account
+pub fn signer_client(client: &Arc<SignerClient>, wallet: LocalWallet) -> Arc<SignerClient> {
+    let wallet = wallet.with_chain_id(client.signer().chain_id());
+    Arc::new(SignerMiddleware::new(client.provider().clone(), wallet))
+}
+
+/// Put the batch taker runtime at `BATCH_TAKER_ADDRESS` on the fork
+pub async fn deploy_batch_taker(client: &Arc<SignerClient>) -> Result<Address> {
+    let address: Address = BATCH_TAKER_ADDRESS.parse()?;
+    client.provider()
+        .request::<_, ()>("anvil_setCode", (address, Bytes::from_str(BATCH_TAKER_RUNTIME.trim())?))
+        .await?;
+    info!("🧰 Batch taker deployed at {:?}", address);
+    Ok(address)
+}
+
+/// Let `account` send unsigned transactions on the fork, topping it up with gas money
+pub async fn impersonate_account(client: &Arc<SignerClient>, account: Address) -> Result<()> {
+    let provider = client.provider();
+    provider.request::<_, ()>("anvil_impersonateAccount", [account]).await?;
+    let gas_money = U256::exp10(18);
+    if client.get_balance(account, None).await? < gas_money {
+        provider.request::<_, ()>("anvil_setBalance", (account, format!("0x{:x}", gas_money))).await?;
+    }
+    Ok(())
+}
+
 pub async fn get_token_balance(
     client: &Arc<SignerClient>,
     token_address: Address,
     account: Address,
 ) -> Result<U256> {
-    let token_abi: ethers::abi::Abi = serde_json::from_str(ERC20_ABI)?;
-    let token_contract = Contract::new(token_address, token_abi, client.clone());
-
-    let balance: U256 = token_contract
-        .method("balanceOf", account)?
-        .call()
-        .await?;
+    let token_contract = IERC20::new(token_address, client.clone());
+    let balance = token_contract.balance_of(account).call().await?;
 
     Ok(balance)
 }
 
+pub async fn get_token_allowance(
+    client: &Arc<SignerClient>,
+    token_address: Address,
+    owner: Address,
+    spender: Address,
+) -> Result<U256> {
+    let token_contract = IERC20::new(token_address, client.clone());
+    let allowance = token_contract.allowance(owner, spender).call().await?;
+
+    Ok(allowance)
+}
+
 pub async fn approve_token(
     client: &Arc<SignerClient>,
     token_address: Address,
     spender: Address,
     amount: U256,
 ) -> Result<()> {
-    let token_abi: ethers::abi::Abi = serde_json::from_str(ERC20_ABI)?;
-    let token_contract = Contract::new(token_address, token_abi, client.clone());
+    approve_token_from(client, token_address, client.address(), spender, amount).await
+}
+
+/// `approve_token` sent from `owner`: the signer, or an account impersonated on the fork
+pub async fn approve_token_from(
+    client: &Arc<SignerClient>,
+    token_address: Address,
+    owner: Address,
+    spender: Address,
+    amount: U256,
+) -> Result<()> {
+    let token_contract = IERC20::new(token_address, client.clone());
 
     let _tx = token_contract
-        .method::<_, bool>("approve", (spender, amount))?
+        .approve(spender, amount)
+        .from(owner)
         .send()
         .await?
         .await?;
@@ -102,56 +160,19 @@ pub async fn set_token_balance_anvil(
     account: Address,
     amount: U256,
 ) -> Result<()> {
-    // Try more storage slots for different token implementations
-    for slot in 0..10 {
-        match set_erc20_balance(client, token_address, account, amount, slot).await {
-            Ok(_) => {
-                // Verify the balance was set
-                let new_balance = get_token_balance(client, token_address, account).await?;
-                
-                if new_balance >= amount {
-                    return Ok(());
-                }
-            }
-            Err(e) => {
-                error!("⚠️  Slot {} failed: {}", slot, e);
-                continue;
-            }
-        }
-    }
-    
-    Err(eyre::eyre!("Failed to set token balance using any common slot"))
-}
+    // Locate the balance slot (cached per chain + token) and overwrite it
+    let slot = find_balance_slot(client, token_address, account).await?;
 
-async fn set_erc20_balance(
-    client: &Arc<SignerClient>,
-    token_address: Address,
-    account: Address,
-    amount: U256,
-    balance_slot: u8,
-) -> Result<()> {
-    use ethers::utils::keccak256;
-    
-    // Calculate storage slot for balance: keccak256(account + balance_slot)
-    let mut key = [0u8; 64];
-    key[12..32].copy_from_slice(account.as_bytes()); // account (20 bytes, right-padded to 32)
-    key[63] = balance_slot; // slot number in last byte
-    
-    let storage_key = keccak256(&key);
-    
-    // Convert amount to 32-byte array
     let mut value = [0u8; 32];
     amount.to_big_endian(&mut value);
-    
-    // Use Anvil's setStorageAt RPC call
-    let provider = client.provider();
-    let _result: bool = provider
-        .request("anvil_setStorageAt", [
-            format!("0x{}", hex::encode(token_address.as_bytes())),
-            format!("0x{}", hex::encode(storage_key)),
-            format!("0x{}", hex::encode(value)),
-        ])
-        .await?;
-    
+    set_storage_at(client, token_address, slot.storage_key(account), H256::from(value)).await?;
+
+    // Verify the balance was set
+    let new_balance = get_token_balance(client, token_address, account).await?;
+    if new_balance != amount {
+        error!("⚠️  Balance of {:?} is {} after writing {:?} slot {}", token_address, new_balance, slot.layout, slot.slot);
+        return Err(eyre::eyre!("Failed to set token balance: balanceOf returned {} instead of {}", new_balance, amount));
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,362 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use ethers::{
+    providers::Middleware,
+    signers::Signer,
+    types::{
+        Address, Bytes, H256, TransactionRequest, U256,
+        GethDebugTracingOptions, GethDebugTracingCallOptions, GethTrace, GethTraceFrame,
+    },
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+use eyre::Result;
+use tracing::{info, debug, warn};
+
+use crate::anvil_setup::SignerClient;
+use crate::bindings::erc20::IERC20;
+
+/// EIP-1967 implementation slot: keccak256("eip1967.proxy.implementation") - 1
+pub const EIP1967_IMPLEMENTATION_SLOT: &str = "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// Default location of the discovered-slot cache (override with BALANCE_SLOT_CACHE)
+const DEFAULT_CACHE_PATH: &str = "balance_slots.json";
+
+// Search bounds for matching a traced key back to a mapping declaration
+const MAX_MAPPING_SLOT: u64 = 256;
+const MAX_STRUCT_OFFSET: u64 = 4;
+// Slots brute-forced when the node does not support debug_traceCall
+const MAX_BRUTE_FORCE_SLOT: u64 = 32;
+
+/// Recognisable value written while probing a storage key
+const PROBE_VALUE: u64 = 0x1337_c0de_beef;
+
+/// How the balances mapping hashes its key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlotLayout {
+    /// keccak256(key . slot)
+    Solidity,
+    /// keccak256(slot . key)
+    Vyper,
+}
+
+/// Location of an ERC20 balance in token storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceSlot {
+    pub layout: SlotLayout,
+    pub slot: u64,                         // Declaration slot of the balances mapping
+    pub offset: u64,                       // Field offset when the mapping value is a struct
+    pub implementation: Option<Address>,   // EIP-1967 implementation the slot was found under
+}
+
+impl BalanceSlot {
+    /// Storage key holding `account`'s balance
+    pub fn storage_key(&self, account: Address) -> H256 {
+        let base = mapping_key(self.layout, self.slot, account);
+        let key = U256::from_big_endian(base.as_bytes()).overflowing_add(U256::from(self.offset)).0;
+        u256_to_h256(key)
+    }
+}
+
+/// JSON cache of discovered slots keyed by "<chain id>:<token address>"
+#[derive(Debug, Default)]
+pub struct BalanceSlotCache {
+    path: PathBuf,
+    entries: BTreeMap<String, BalanceSlot>,
+}
+
+impl BalanceSlotCache {
+    /// Load the cache at `path`, starting empty if the file does not exist
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let entries = if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            serde_json::from_str(&content)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { path, entries })
+    }
+
+    pub fn get(&self, chain_id: u64, token: Address) -> Option<BalanceSlot> {
+        self.entries.get(&Self::key(chain_id, token)).copied()
+    }
+
+    /// Record a slot and persist the cache
+    pub fn insert(&mut self, chain_id: u64, token: Address, slot: BalanceSlot) -> Result<()> {
+        self.entries.insert(Self::key(chain_id, token), slot);
+        let content = serde_json::to_string_pretty(&self.entries)?;
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+
+    fn key(chain_id: u64, token: Address) -> String {
+        format!("{}:{:?}", chain_id, token)
+    }
+}
+
+/// Process-wide cache shared by every fork
+fn shared_cache() -> &'static Mutex<BalanceSlotCache> {
+    static CACHE: OnceLock<Mutex<BalanceSlotCache>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        let path = std::env::var("BALANCE_SLOT_CACHE").unwrap_or_else(|_| DEFAULT_CACHE_PATH.to_string());
+        let cache = BalanceSlotCache::load(&path).unwrap_or_else(|e| {
+            warn!("⚠️  Ignoring unreadable balance slot cache {}: {}", path, e);
+            BalanceSlotCache { path: path.into(), entries: BTreeMap::new() }
+        });
+        Mutex::new(cache)
+    })
+}
+
+/// Find where `token` stores balances, using the cache when the proxy implementation is unchanged
+pub async fn find_balance_slot(
+    client: &Arc<SignerClient>,
+    token: Address,
+    account: Address,
+) -> Result<BalanceSlot> {
+    let chain_id = client.signer().chain_id();
+    let implementation = eip1967_implementation(client, token).await?;
+
+    let cached = shared_cache().lock().unwrap().get(chain_id, token);
+    if let Some(slot) = cached {
+        if slot.implementation == implementation {
+            debug!("Using cached balance slot for {:?}: {:?}", token, slot);
+            return Ok(slot);
+        }
+        info!("🔁 Implementation of {:?} changed, rediscovering balance slot", token);
+    }
+
+    let slot = discover_balance_slot(client, token, account, implementation).await?;
+    info!("🔎 Balance slot for {:?}: {:?} slot {} offset {}", token, slot.layout, slot.slot, slot.offset);
+
+    if let Err(e) = shared_cache().lock().unwrap().insert(chain_id, token, slot) {
+        warn!("⚠️  Failed to persist balance slot cache: {}", e);
+    }
+    Ok(slot)
+}
+
+/// Discover the balance slot by tracing `balanceOf`, falling back to a layout brute force
+async fn discover_balance_slot(
+    client: &Arc<SignerClient>,
+    token: Address,
+    account: Address,
+    implementation: Option<Address>,
+) -> Result<BalanceSlot> {
+    let data = IERC20::new(token, client.clone())
+        .balance_of(account)
+        .calldata()
+        .ok_or_else(|| eyre::eyre!("Failed to encode balanceOf()"))?;
+
+    match traced_sload_keys(client, token, data.clone()).await {
+        Ok(keys) => {
+            // The balance read is usually the last SLOAD before returning
+            for key in keys.iter().rev() {
+                if !probe_storage_key(client, token, *key, &data).await? {
+                    continue;
+                }
+                if let Some((layout, slot, offset)) = match_mapping_key(*key, account) {
+                    return Ok(BalanceSlot { layout, slot, offset, implementation });
+                }
+                debug!("Key {:?} holds the balance but matches no known mapping layout", key);
+            }
+        }
+        Err(e) => debug!("debug_traceCall unavailable, brute forcing slots: {}", e),
+    }
+
+    for slot in 0..MAX_BRUTE_FORCE_SLOT {
+        for layout in [SlotLayout::Solidity, SlotLayout::Vyper] {
+            let candidate = BalanceSlot { layout, slot, offset: 0, implementation };
+            if probe_storage_key(client, token, candidate.storage_key(account), &data).await? {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    Err(eyre::eyre!("Could not locate balance storage slot for token {:?}", token))
+}
+
+/// Storage keys read by a call to `token` with `data`, in execution order
+async fn traced_sload_keys(client: &Arc<SignerClient>, token: Address, data: Bytes) -> Result<Vec<H256>> {
+    let tx = TransactionRequest::new().to(token).data(data);
+
+    let options = GethDebugTracingCallOptions {
+        tracing_options: GethDebugTracingOptions {
+            disable_storage: Some(true),
+            enable_memory: Some(false),
+            ..Default::default()
+        },
+        state_overrides: None,
+        block_overrides: None,
+    };
+
+    let trace = client.provider().debug_trace_call(tx, None, options).await?;
+    let frame = match trace {
+        GethTrace::Known(GethTraceFrame::Default(frame)) => frame,
+        other => return Err(eyre::eyre!("Unexpected trace format: {:?}", other)),
+    };
+
+    let mut keys: Vec<H256> = Vec::new();
+    for log in frame.struct_logs.iter().filter(|l| l.op == "SLOAD") {
+        if let Some(key) = log.stack.as_ref().and_then(|s| s.last()) {
+            let key = u256_to_h256(*key);
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Whether writing `key` changes the uint returned by calling `token` with `data`;
+/// the original value is always restored
+async fn probe_storage_key(client: &Arc<SignerClient>, token: Address, key: H256, data: &Bytes) -> Result<bool> {
+    let original = client.get_storage_at(token, key, None).await?;
+    let probe = U256::from(PROBE_VALUE);
+
+    set_storage_at(client, token, key, u256_to_h256(probe)).await?;
+    let tx = TransactionRequest::new().to(token).data(data.clone());
+    let returned = client.call(&tx.into(), None).await;
+    set_storage_at(client, token, key, original).await?;
+
+    Ok(matches!(returned, Ok(ret) if ret.len() >= 32 && U256::from_big_endian(&ret[..32]) == probe))
+}
+
+/// Storage key of `totalSupply`, found by tracing `totalSupply()`
+pub async fn find_total_supply_key(client: &Arc<SignerClient>, token: Address) -> Result<H256> {
+    let data = IERC20::new(token, client.clone())
+        .total_supply()
+        .calldata()
+        .ok_or_else(|| eyre::eyre!("Failed to encode totalSupply()"))?;
+    let keys = traced_sload_keys(client, token, data.clone()).await?;
+
+    for key in keys.iter().rev() {
+        if probe_storage_key(client, token, *key, &data).await? {
+            return Ok(*key);
+        }
+    }
+    Err(eyre::eyre!("Could not locate totalSupply storage slot for token {:?}", token))
+}
+
+
+/// Match a storage key to `keccak(mapping) + offset` for Solidity or Vyper layouts
+fn match_mapping_key(key: H256, account: Address) -> Option<(SlotLayout, u64, u64)> {
+    let key = U256::from_big_endian(key.as_bytes());
+    for slot in 0..MAX_MAPPING_SLOT {
+        for layout in [SlotLayout::Solidity, SlotLayout::Vyper] {
+            let base = U256::from_big_endian(mapping_key(layout, slot, account).as_bytes());
+            let (offset, underflow) = key.overflowing_sub(base);
+            if !underflow && offset < U256::from(MAX_STRUCT_OFFSET) {
+                return Some((layout, slot, offset.as_u64()));
+            }
+        }
+    }
+    None
+}
+
+fn mapping_key(layout: SlotLayout, slot: u64, account: Address) -> H256 {
+    let account_word = H256::from(account);
+    let slot_word = u256_to_h256(U256::from(slot));
+
+    let mut preimage = [0u8; 64];
+    match layout {
+        SlotLayout::Solidity => {
+            preimage[..32].copy_from_slice(account_word.as_bytes());
+            preimage[32..].copy_from_slice(slot_word.as_bytes());
+        }
+        SlotLayout::Vyper => {
+            preimage[..32].copy_from_slice(slot_word.as_bytes());
+            preimage[32..].copy_from_slice(account_word.as_bytes());
+        }
+    }
+    H256::from(keccak256(preimage))
+}
+
+/// Implementation address behind an EIP-1967 proxy, if `token` is one
+pub async fn eip1967_implementation(client: &Arc<SignerClient>, token: Address) -> Result<Option<Address>> {
+    let slot: H256 = EIP1967_IMPLEMENTATION_SLOT.parse()?;
+    let value = client.get_storage_at(token, slot, None).await?;
+    let implementation = Address::from_slice(&value.as_bytes()[12..]);
+    Ok((implementation != Address::zero()).then_some(implementation))
+}
+
+/// Write a raw storage word with `anvil_setStorageAt`
+pub async fn set_storage_at(client: &Arc<SignerClient>, address: Address, key: H256, value: H256) -> Result<()> {
+    let _result: bool = client
+        .provider()
+        .request("anvil_setStorageAt", (address, key, value))
+        .await?;
+    Ok(())
+}
+
+fn u256_to_h256(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256::from(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account() -> Address {
+        "0x8894e0a0c962cb723c1976a4421c95949be2d4e3".parse().unwrap()
+    }
+
+    fn slot(layout: SlotLayout, slot: u64, offset: u64) -> BalanceSlot {
+        BalanceSlot { layout, slot, offset, implementation: None }
+    }
+
+    fn key(hex: &str) -> H256 {
+        hex.parse().unwrap()
+    }
+
+    #[test]
+    fn storage_keys() {
+        // keccak256(abi.encode(0, 0)), the same for both layouts
+        let zero = key("0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5");
+        assert_eq!(slot(SlotLayout::Solidity, 0, 0).storage_key(Address::zero()), zero);
+        assert_eq!(slot(SlotLayout::Vyper, 0, 0).storage_key(Address::zero()), zero);
+
+        let solidity_0 = key("0xc93cc1c70b0ec7038a4601b41834642a73cd6fabb051db5063a555bb3a098911");
+        let solidity_1 = key("0x2a1e20cfdc0a1a03b0a1a44fdd9fd5fa930358e5b8221476467d529192e1b4b4");
+        let vyper_1 = key("0x773edd5eb2f2e1c026fde3f08114a593462b36d3fec727da06b29d4c25d80a82");
+        assert_eq!(slot(SlotLayout::Solidity, 0, 0).storage_key(account()), solidity_0);
+        assert_eq!(slot(SlotLayout::Solidity, 1, 0).storage_key(account()), solidity_1);
+        assert_eq!(slot(SlotLayout::Vyper, 1, 0).storage_key(account()), vyper_1);
+        assert_eq!(
+            slot(SlotLayout::Solidity, 1, 2).storage_key(account()),
+            key("0x2a1e20cfdc0a1a03b0a1a44fdd9fd5fa930358e5b8221476467d529192e1b4b6")
+        );
+    }
+
+    #[test]
+    fn traced_keys_match_their_mapping() {
+        for candidate in [slot(SlotLayout::Solidity, 51, 0), slot(SlotLayout::Vyper, 3, 0), slot(SlotLayout::Solidity, 9, 1)] {
+            let matched = match_mapping_key(candidate.storage_key(account()), account());
+            assert_eq!(matched, Some((candidate.layout, candidate.slot, candidate.offset)));
+        }
+        assert_eq!(match_mapping_key(H256::repeat_byte(0x11), account()), None);
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("balance_slots.json");
+        let (token, proxy) = (Address::repeat_byte(0x55), Address::repeat_byte(0x66));
+        let proxied = BalanceSlot { implementation: Some(Address::repeat_byte(0x77)), ..slot(SlotLayout::Vyper, 3, 1) };
+
+        let mut cache = BalanceSlotCache::load(&path).unwrap();
+        assert_eq!(cache.get(56, token), None);
+        cache.insert(56, token, slot(SlotLayout::Solidity, 1, 0)).unwrap();
+        cache.insert(56, proxy, proxied).unwrap();
+
+        let reloaded = BalanceSlotCache::load(&path).unwrap();
+        assert_eq!(reloaded.get(56, token), Some(slot(SlotLayout::Solidity, 1, 0)));
+        assert_eq!(reloaded.get(56, proxy), Some(proxied));
+        assert_eq!(reloaded.get(1, token), None);
+        assert!(fs::read_to_string(&path).unwrap().contains("\"vyper\""));
+    }
+}
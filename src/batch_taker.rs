@@ -0,0 +1,230 @@
+use ethers::types::{Address, Bytes, U256};
+use eyre::Result;
+
+/// Where `anvil_setup::deploy_batch_taker` places the executor on the fork
+pub const BATCH_TAKER_ADDRESS: &str = "0x0000000000000000000000000000000000ba7c11";
+
+/// Runtime code of the batch taker
+///
+/// A minimal multicall executor compiled from `contracts/BatchTaker.sol` with solc 0.8.24;
+/// the regeneration command is in the contract's header. It runs a packed list of calls taken
+/// from `execute(bytes calls)`, or from `extraData` when the router calls it as a taker
+/// interaction (`takerInteraction(..., bytes extraData)`). Each entry is
+///
+/// ```text
+/// [address target (20)][uint8 allowFailure (1)][uint32 length (4)][padding (7)][uint256 value (32)][data (length)]
+/// ```
+///
+/// Each entry is called from the executor with `value` wei. A failing call with
+/// `allowFailure == 0` reverts the whole batch with that call's revert data, so router
+/// errors decode as if the router had been called directly. Otherwise it returns
+/// `bytes results`, one `[uint256 success][uint256 length][returndata (length)]` per call.
+/// There is no access control: it is only ever deployed on a fork.
+pub const BATCH_TAKER_RUNTIME: &str = include_str!("../contracts/BatchTaker.bin-runtime");
+
+/// One call made by the batch taker
+#[derive(Debug, Clone)]
+pub struct BatchCall {
+    pub target: Address,
+    pub value: U256,
+    /// Keep going (and report the failure) instead of reverting the batch
+    pub allow_failure: bool,
+    pub data: Bytes,
+}
+
+/// Success flag and return (or revert) data of one batch call
+#[derive(Debug, Clone)]
+pub struct CallResult {
+    pub success: bool,
+    pub return_data: Bytes,
+}
+
+/// Pack `calls` into the `calls` argument of `execute`
+pub fn encode_calls(calls: &[BatchCall]) -> Bytes {
+    let mut packed = Vec::new();
+    for call in calls {
+        let mut header = [0u8; 32];
+        header[..20].copy_from_slice(call.target.as_bytes());
+        header[20] = call.allow_failure as u8;
+        header[21..25].copy_from_slice(&(call.data.len() as u32).to_be_bytes());
+        packed.extend_from_slice(&header);
+
+        let mut value = [0u8; 32];
+        call.value.to_big_endian(&mut value);
+        packed.extend_from_slice(&value);
+        packed.extend_from_slice(&call.data);
+    }
+    packed.into()
+}
+
+/// Split the `results` returned by `execute`
+pub fn decode_results(data: &[u8]) -> Result<Vec<CallResult>> {
+    let mut results = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        if rest.len() < 64 {
+            return Err(eyre::eyre!("Truncated batch result header ({} bytes)", rest.len()));
+        }
+        let success = !U256::from_big_endian(&rest[..32]).is_zero();
+        let length = U256::from_big_endian(&rest[32..64]);
+        if length > U256::from(rest.len() - 64) {
+            return Err(eyre::eyre!("Batch result claims {} bytes, {} left", length, rest.len() - 64));
+        }
+        let length = length.as_usize();
+        results.push(CallResult { success, return_data: Bytes::from(rest[64..64 + length].to_vec()) });
+        rest = &rest[64 + length..];
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use ethers::abi::{decode, encode, ParamType, Token};
+    use ethers::providers::Middleware;
+    use ethers::types::TransactionRequest;
+    use ethers::utils::id;
+    use crate::anvil_setup::{SignerClient, deploy_batch_taker, setup_blockchain};
+    use crate::bindings::batch_taker::BatchTaker;
+    use crate::config::fork_profile::ForkProfile;
+    use super::*;
+
+    const TAKER_INTERACTION: &str = "takerInteraction((uint256,uint256,uint256,uint256,uint256,uint256,uint256,uint256),bytes,bytes32,address,uint256,uint256,uint256,bytes)";
+    /// `return(calldata)`
+    const ECHO: &str = "0x366000600037366000f3";
+    /// `revert(calldata)`
+    const REVERTER: &str = "0x366000600037366000fd";
+
+    fn batch_call(target: u64, value: u64, allow_failure: bool, data: &[u8]) -> BatchCall {
+        BatchCall { target: Address::from_low_u64_be(target), value: U256::from(value), allow_failure, data: data.to_vec().into() }
+    }
+
+    /// Local anvil with the executor, an echo contract at 0xaaaa and a reverter at 0xbbbb
+    async fn executor_on_anvil() -> (ethers::utils::AnvilInstance, Arc<SignerClient>, Address) {
+        let (anvil, client) = setup_blockchain(&ForkProfile::local()).await.unwrap();
+        let executor = deploy_batch_taker(&client).await.unwrap();
+        for (address, code) in [(0xaaaa, ECHO), (0xbbbb, REVERTER)] {
+            client.provider()
+                .request::<_, ()>("anvil_setCode", (Address::from_low_u64_be(address), code))
+                .await
+                .unwrap();
+        }
+        client.provider()
+            .request::<_, ()>("anvil_setBalance", (executor, format!("0x{:x}", U256::exp10(18))))
+            .await
+            .unwrap();
+        (anvil, client, executor)
+    }
+
+    fn results_of(output: &[u8]) -> Vec<CallResult> {
+        let results = decode(&[ParamType::Bytes], output).unwrap().remove(0).into_bytes().unwrap();
+        decode_results(&results).unwrap()
+    }
+
+    #[test]
+    fn taker_interaction_selector() {
+        assert_eq!(id(TAKER_INTERACTION), [0xad, 0xf3, 0x8b, 0xa1]);
+    }
+
+    #[test]
+    fn encode_calls_layout() {
+        let packed = encode_calls(&[batch_call(0xaaaa, 5, true, b"hi")]);
+        assert_eq!(packed.len(), 66);
+        assert_eq!(&packed[..20], Address::from_low_u64_be(0xaaaa).as_bytes());
+        assert_eq!(packed[20], 1);
+        assert_eq!(&packed[21..25], &2u32.to_be_bytes());
+        assert_eq!(&packed[25..32], &[0u8; 7]);
+        assert_eq!(U256::from_big_endian(&packed[32..64]), U256::from(5));
+        assert_eq!(&packed[64..], b"hi");
+    }
+
+    #[test]
+    fn decode_results_rejects_truncated_data() {
+        let mut data = vec![0u8; 64];
+        data[31] = 1;
+        data[63] = 10;
+        data.extend_from_slice(b"short");
+        assert!(decode_results(&data).is_err());
+        assert!(decode_results(&data[..40]).is_err());
+    }
+
+    #[test]
+    #[ignore = "needs solc 0.8.24 on PATH"]
+    fn runtime_matches_solc_output() {
+        let output = Command::new("solc")
+            .args(["--optimize", "--optimize-runs", "200", "--evm-version", "shanghai", "--metadata-hash", "none"])
+            .args(["--bin-runtime", concat!(env!("CARGO_MANIFEST_DIR"), "/contracts/BatchTaker.sol")])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let compiled = String::from_utf8(output.stdout).unwrap();
+        let compiled = compiled.lines().last().unwrap().trim();
+        assert_eq!(Bytes::from_str(compiled).unwrap(), Bytes::from_str(BATCH_TAKER_RUNTIME.trim()).unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "spawns anvil"]
+    async fn execute_on_anvil() {
+        let (_anvil, client, executor) = executor_on_anvil().await;
+        let calls = [
+            batch_call(0xaaaa, 0, false, b"hello world"),
+            batch_call(0xbbbb, 0, true, b"nope"),
+            batch_call(0xcccc, 5, false, b""),
+        ];
+        let call = BatchTaker::new(executor, client.clone()).execute(encode_calls(&calls));
+
+        let results = decode_results(&call.call().await.unwrap()).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].success);
+        assert_eq!(results[0].return_data.to_vec(), b"hello world");
+        assert!(!results[1].success);
+        assert_eq!(results[1].return_data.to_vec(), b"nope");
+        assert!(results[2].success && results[2].return_data.is_empty());
+
+        call.send().await.unwrap().await.unwrap();
+        assert_eq!(client.get_balance(Address::from_low_u64_be(0xcccc), None).await.unwrap(), U256::from(5));
+    }
+
+    #[tokio::test]
+    #[ignore = "spawns anvil"]
+    async fn failing_call_reverts_the_batch() {
+        let (_anvil, client, executor) = executor_on_anvil().await;
+        let calls = [batch_call(0xaaaa, 0, false, b"ok"), batch_call(0xbbbb, 0, false, b"\x08\xc3\x79\xa0nope")];
+        let error = BatchTaker::new(executor, client).execute(encode_calls(&calls)).call().await.unwrap_err();
+        assert_eq!(error.as_revert().map(|data| data.to_vec()), Some(b"\x08\xc3\x79\xa0nope".to_vec()));
+    }
+
+    #[tokio::test]
+    #[ignore = "spawns anvil"]
+    async fn empty_batch() {
+        let (_anvil, client, executor) = executor_on_anvil().await;
+        let results = BatchTaker::new(executor, client).execute(Bytes::new()).call().await.unwrap();
+        assert!(decode_results(&results).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "spawns anvil"]
+    async fn taker_interaction_runs_extra_data() {
+        let (_anvil, client, executor) = executor_on_anvil().await;
+        let calls = [batch_call(0xaaaa, 0, false, b"approve"), batch_call(0xaaaa, 0, false, b"swap")];
+        let order = Token::Tuple((1..=8).map(|i| Token::Uint(U256::from(i))).collect());
+        let mut calldata = id(TAKER_INTERACTION).to_vec();
+        calldata.extend(encode(&[
+            order,
+            Token::Bytes(b"extension".to_vec()),
+            Token::FixedBytes(vec![0x11; 32]),
+            Token::Address(Address::from_low_u64_be(0xbeef)),
+            Token::Uint(U256::from(100)),
+            Token::Uint(U256::from(200)),
+            Token::Uint(U256::from(300)),
+            Token::Bytes(encode_calls(&calls).to_vec()),
+        ]));
+
+        let tx = TransactionRequest::new().to(executor).data(calldata);
+        let results = results_of(&client.call(&tx.into(), None).await.unwrap());
+        let returned: Vec<Vec<u8>> = results.iter().map(|r| r.return_data.to_vec()).collect();
+        assert_eq!(returned, vec![b"approve".to_vec(), b"swap".to_vec()]);
+    }
+}
@@ -0,0 +1,304 @@
+// Typed contract bindings generated with abigen
+//
+// Each binding lives in its own module so event and struct names generated by
+// abigen (e.g. `TransferFilter`, `SwapFilter`) do not collide.
+
+/// Standard ERC20 token
+pub mod erc20 {
+    ethers::contract::abigen!(
+        IERC20,
+        r#"[
+            function name() external view returns (string)
+            function symbol() external view returns (string)
+            function decimals() external view returns (uint8)
+            function totalSupply() external view returns (uint256)
+            function balanceOf(address account) external view returns (uint256)
+            function allowance(address owner, address spender) external view returns (uint256)
+            function approve(address spender, uint256 amount) external returns (bool)
+            function transfer(address to, uint256 amount) external returns (bool)
+            function transferFrom(address from, address to, uint256 amount) external returns (bool)
+            event Transfer(address indexed from, address indexed to, uint256 value)
+            event Approval(address indexed owner, address indexed spender, uint256 value)
+        ]"#
+    );
+}
+
+/// ERC-1271 contract signature validation
+pub mod erc1271 {
+    ethers::contract::abigen!(
+        IERC1271,
+        r#"[
+            function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4)
+        ]"#
+    );
+}
+
+/// EIP-2612 permit extension of ERC20
+pub mod erc20_permit {
+    ethers::contract::abigen!(
+        IERC20Permit,
+        r#"[
+            function DOMAIN_SEPARATOR() external view returns (bytes32)
+            function nonces(address owner) external view returns (uint256)
+            function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external
+        ]"#
+    );
+}
+
+/// Uniswap Permit2
+pub mod permit2 {
+    ethers::contract::abigen!(
+        Permit2,
+        r#"[
+            function DOMAIN_SEPARATOR() external view returns (bytes32)
+            function allowance(address owner, address token, address spender) external view returns (uint160 amount, uint48 expiration, uint48 nonce)
+            function approve(address token, address spender, uint160 amount, uint48 expiration) external
+            function nonceBitmap(address owner, uint256 wordPos) external view returns (uint256)
+        ]"#
+    );
+}
+
+/// Batch taker executor deployed on the fork (see `batch_taker`)
+pub mod batch_taker {
+    ethers::contract::abigen!(
+        BatchTaker,
+        r#"[
+            function execute(bytes calls) external payable returns (bytes results)
+        ]"#
+    );
+}
+
+/// PancakeSwap V2 (Uniswap V2 fork) router
+pub mod pancake_v2_router {
+    ethers::contract::abigen!(
+        PancakeV2Router,
+        r#"[
+            function factory() external view returns (address)
+            function WETH() external view returns (address)
+            function getAmountsOut(uint256 amountIn, address[] path) external view returns (uint256[] amounts)
+            function getAmountsIn(uint256 amountOut, address[] path) external view returns (uint256[] amounts)
+            function swapExactETHForTokens(uint256 amountOutMin, address[] path, address to, uint256 deadline) external payable returns (uint256[] amounts)
+            function swapETHForExactTokens(uint256 amountOut, address[] path, address to, uint256 deadline) external payable returns (uint256[] amounts)
+            function swapExactTokensForETH(uint256 amountIn, uint256 amountOutMin, address[] path, address to, uint256 deadline) external returns (uint256[] amounts)
+            function swapExactTokensForTokens(uint256 amountIn, uint256 amountOutMin, address[] path, address to, uint256 deadline) external returns (uint256[] amounts)
+            function swapTokensForExactTokens(uint256 amountOut, uint256 amountInMax, address[] path, address to, uint256 deadline) external returns (uint256[] amounts)
+            function swapExactTokensForTokensSupportingFeeOnTransferTokens(uint256 amountIn, uint256 amountOutMin, address[] path, address to, uint256 deadline) external
+        ]"#
+    );
+}
+
+/// PancakeSwap V2 pair
+pub mod pancake_v2_pair {
+    ethers::contract::abigen!(
+        PancakeV2Pair,
+        r#"[
+            function factory() external view returns (address)
+            function token0() external view returns (address)
+            function token1() external view returns (address)
+            function getReserves() external view returns (uint112 _reserve0, uint112 _reserve1, uint32 _blockTimestampLast)
+            function swap(uint256 amount0Out, uint256 amount1Out, address to, bytes data) external
+            event Swap(address indexed sender, uint256 amount0In, uint256 amount1In, uint256 amount0Out, uint256 amount1Out, address indexed to)
+            event Sync(uint112 reserve0, uint112 reserve1)
+        ]"#
+    );
+}
+
+/// PancakeSwap V2 factory
+pub mod pancake_v2_factory {
+    ethers::contract::abigen!(
+        PancakeV2Factory,
+        r#"[
+            function getPair(address tokenA, address tokenB) external view returns (address pair)
+            function allPairsLength() external view returns (uint256)
+            function feeTo() external view returns (address)
+            event PairCreated(address indexed token0, address indexed token1, address pair, uint256)
+        ]"#
+    );
+}
+
+/// PancakeSwap V3 (Uniswap V3 fork) swap router
+pub mod pancake_v3_router {
+    ethers::contract::abigen!(
+        PancakeV3Router,
+        r#"[
+            struct ExactInputSingleParams { address tokenIn; address tokenOut; uint24 fee; address recipient; uint256 deadline; uint256 amountIn; uint256 amountOutMinimum; uint160 sqrtPriceLimitX96; }
+            struct ExactOutputSingleParams { address tokenIn; address tokenOut; uint24 fee; address recipient; uint256 deadline; uint256 amountOut; uint256 amountInMaximum; uint160 sqrtPriceLimitX96; }
+            function exactInputSingle(ExactInputSingleParams params) external payable returns (uint256 amountOut)
+            function exactOutputSingle(ExactOutputSingleParams params) external payable returns (uint256 amountIn)
+            function refundETH() external payable
+            function selfPermit(address token, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external payable
+        ]"#
+    );
+}
+
+/// PancakeSwap V3 (Uniswap V3 fork) pool
+pub mod pancake_v3_pool {
+    ethers::contract::abigen!(
+        PancakeV3Pool,
+        r#"[
+            function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked)
+            function liquidity() external view returns (uint128)
+            function token0() external view returns (address)
+            function token1() external view returns (address)
+            function fee() external view returns (uint24)
+        ]"#
+    );
+}
+
+/// PancakeSwap V3 QuoterV2
+pub mod pancake_v3_quoter {
+    ethers::contract::abigen!(
+        PancakeV3Quoter,
+        r#"[
+            struct QuoteExactInputSingleParams { address tokenIn; address tokenOut; uint256 amountIn; uint24 fee; uint160 sqrtPriceLimitX96; }
+            struct QuoteExactOutputSingleParams { address tokenIn; address tokenOut; uint256 amount; uint24 fee; uint160 sqrtPriceLimitX96; }
+            function quoteExactInputSingle(QuoteExactInputSingleParams params) external returns (uint256 amountOut, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
+            function quoteExactOutputSingle(QuoteExactOutputSingleParams params) external returns (uint256 amountIn, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
+            function quoteExactInput(bytes path, uint256 amountIn) external returns (uint256 amountOut, uint160[] sqrtPriceX96AfterList, uint32[] initializedTicksCrossedList, uint256 gasEstimate)
+            function quoteExactOutput(bytes path, uint256 amountOut) external returns (uint256 amountIn, uint160[] sqrtPriceX96AfterList, uint32[] initializedTicksCrossedList, uint256 gasEstimate)
+        ]"#
+    );
+}
+
+/// 1inch AggregationRouterV6 (limit order protocol v4 + swaps)
+pub mod aggregation_router_v6 {
+    ethers::contract::abigen!(
+        AggregationRouterV6,
+        r#"[
+            struct Order { uint256 salt; uint256 maker; uint256 receiver; uint256 makerAsset; uint256 takerAsset; uint256 makingAmount; uint256 takingAmount; uint256 makerTraits; }
+            struct SwapDescription { address srcToken; address dstToken; address srcReceiver; address dstReceiver; uint256 amount; uint256 minReturnAmount; uint256 flags; }
+
+            function fillOrder(Order order, bytes32 r, bytes32 vs, uint256 amount, uint256 takerTraits) external payable returns (uint256, uint256, bytes32)
+            function fillOrderArgs(Order order, bytes32 r, bytes32 vs, uint256 amount, uint256 takerTraits, bytes args) external payable returns (uint256, uint256, bytes32)
+            function fillContractOrder(Order order, bytes signature, uint256 amount, uint256 takerTraits) external returns (uint256, uint256, bytes32)
+            function fillContractOrderArgs(Order order, bytes signature, uint256 amount, uint256 takerTraits, bytes args) external returns (uint256, uint256, bytes32)
+            function hashOrder(Order order) external view returns (bytes32)
+            function permitAndCall(bytes permit, bytes action) external payable
+            function simulate(address target, bytes data) external
+
+            function remainingInvalidatorForOrder(address maker, bytes32 orderHash) external view returns (uint256)
+            function rawRemainingInvalidatorForOrder(address maker, bytes32 orderHash) external view returns (uint256)
+            function bitInvalidatorForOrder(address maker, uint256 slot) external view returns (uint256)
+            function bitsInvalidateForOrder(uint256 makerTraits, uint256 additionalMask) external
+            function cancelOrder(uint256 makerTraits, bytes32 orderHash) external
+            function cancelOrders(uint256[] makerTraits, bytes32[] orderHashes) external
+
+            function epoch(address maker, uint96 series) external view returns (uint256)
+            function epochEquals(address maker, uint256 series, uint256 makerEpoch) external view returns (bool)
+            function increaseEpoch(uint96 series) external
+            function advanceEpoch(uint96 series, uint256 amount) external
+
+            function checkPredicate(bytes predicate) external view returns (bool)
+            function and(uint256 offsets, bytes data) external view returns (bool)
+            function or(uint256 offsets, bytes data) external view returns (bool)
+            function not(bytes data) external view returns (bool)
+            function eq(uint256 value, bytes data) external view returns (bool)
+            function lt(uint256 value, bytes data) external view returns (bool)
+            function gt(uint256 value, bytes data) external view returns (bool)
+            function arbitraryStaticCall(address target, bytes data) external view returns (uint256)
+
+            function swap(address executor, SwapDescription desc, bytes data) external payable returns (uint256 returnAmount, uint256 spentAmount)
+            function unoswap(uint256 token, uint256 amount, uint256 minReturn, uint256 dex) external returns (uint256 returnAmount)
+            function unoswap2(uint256 token, uint256 amount, uint256 minReturn, uint256 dex, uint256 dex2) external returns (uint256 returnAmount)
+            function unoswap3(uint256 token, uint256 amount, uint256 minReturn, uint256 dex, uint256 dex2, uint256 dex3) external returns (uint256 returnAmount)
+            function unoswapTo(uint256 to, uint256 token, uint256 amount, uint256 minReturn, uint256 dex) external returns (uint256 returnAmount)
+            function unoswapTo2(uint256 to, uint256 token, uint256 amount, uint256 minReturn, uint256 dex, uint256 dex2) external returns (uint256 returnAmount)
+            function unoswapTo3(uint256 to, uint256 token, uint256 amount, uint256 minReturn, uint256 dex, uint256 dex2, uint256 dex3) external returns (uint256 returnAmount)
+            function ethUnoswap(uint256 minReturn, uint256 dex) external payable returns (uint256 returnAmount)
+            function ethUnoswap2(uint256 minReturn, uint256 dex, uint256 dex2) external payable returns (uint256 returnAmount)
+            function ethUnoswap3(uint256 minReturn, uint256 dex, uint256 dex2, uint256 dex3) external payable returns (uint256 returnAmount)
+            function ethUnoswapTo(uint256 to, uint256 minReturn, uint256 dex) external payable returns (uint256 returnAmount)
+            function ethUnoswapTo2(uint256 to, uint256 minReturn, uint256 dex, uint256 dex2) external payable returns (uint256 returnAmount)
+            function ethUnoswapTo3(uint256 to, uint256 minReturn, uint256 dex, uint256 dex2, uint256 dex3) external payable returns (uint256 returnAmount)
+            function clipperSwap(address clipperExchange, uint256 srcToken, address dstToken, uint256 inputAmount, uint256 outputAmount, uint256 goodUntil, bytes32 r, bytes32 vs) external payable returns (uint256 returnAmount)
+            function clipperSwapTo(address clipperExchange, address recipient, uint256 srcToken, address dstToken, uint256 inputAmount, uint256 outputAmount, uint256 goodUntil, bytes32 r, bytes32 vs) external payable returns (uint256 returnAmount)
+            function curveSwapCallback(address, address, address inCoin, uint256 dx, uint256) external
+            function uniswapV3SwapCallback(int256 amount0Delta, int256 amount1Delta, bytes) external
+
+            function eip712Domain() external view returns (bytes1 fields, string name, string version, uint256 chainId, address verifyingContract, bytes32 salt, uint256[] extensions)
+            function owner() external view returns (address)
+            function paused() external view returns (bool)
+            function pause() external
+            function unpause() external
+            function rescueFunds(address token, uint256 amount) external
+            function transferOwnership(address newOwner) external
+            function renounceOwnership() external
+
+            event OrderFilled(bytes32 orderHash, uint256 remainingAmount)
+            event OrderCancelled(bytes32 orderHash)
+            event BitInvalidatorUpdated(address indexed maker, uint256 slotIndex, uint256 slotValue)
+            event EpochIncreased(address indexed maker, uint256 series, uint256 newEpoch)
+            event EIP712DomainChanged()
+            event OwnershipTransferred(address indexed previousOwner, address indexed newOwner)
+            event Paused(address account)
+            event Unpaused(address account)
+
+            error AdvanceEpochFailed()
+            error ArbitraryStaticCallFailed()
+            error BadCurveSwapSelector()
+            error BadPool()
+            error BadSignature()
+            error BitInvalidatedOrder()
+            error ETHTransferFailed()
+            error EnforcedPause()
+            error EpochManagerAndBitInvalidatorsAreIncompatible()
+            error EthDepositRejected()
+            error ExpectedPause()
+            error InsufficientBalance()
+            error InvalidMsgValue()
+            error InvalidPermit2Transfer()
+            error InvalidShortString()
+            error InvalidatedOrder()
+            error MakingAmountTooLow()
+            error MismatchArraysLengths()
+            error OrderExpired()
+            error OrderIsNotSuitableForMassInvalidation()
+            error OwnableInvalidOwner(address owner)
+            error OwnableUnauthorizedAccount(address account)
+            error PartialFillNotAllowed()
+            error Permit2TransferAmountTooHigh()
+            error PredicateIsNotTrue()
+            error PrivateOrder()
+            error ReentrancyDetected()
+            error RemainingInvalidatedOrder()
+            error ReservesCallFailed()
+            error ReturnAmountIsNotEnough(uint256 result, uint256 minReturn)
+            error SafeTransferFailed()
+            error SafeTransferFromFailed()
+            error SimulationResults(bool success, bytes res)
+            error SlippageTooHigh()
+            error StringTooLong(string str)
+            error SwapWithZeroAmount()
+            error TakingAmountExceeded()
+            error TakingAmountTooHigh()
+            error TransferFromMakerToTakerFailed()
+            error TransferFromTakerToMakerFailed()
+            error WrongSeriesNonce()
+            error ZeroAddress()
+            error ZeroMinReturn()
+        ]"#
+    );
+}
+
+/// `permitAndCall` on AggregationRouterV6 wrapping a fill: it returns whatever the delegated
+/// action returns, here `fillOrderArgs`' (makingAmount, takingAmount, orderHash)
+pub mod permit_and_call {
+    ethers::contract::abigen!(
+        PermitAndCallFill,
+        r#"[
+            function permitAndCall(bytes permit, bytes action) external payable returns (uint256, uint256, bytes32)
+        ]"#
+    );
+}
+
+/// IAmountGetter of limit order protocol v4, e.g. DutchAuctionCalculator and RangeAmountCalculator
+pub mod amount_getter {
+    ethers::contract::abigen!(
+        IAmountGetter,
+        r#"[
+            struct Order { uint256 salt; uint256 maker; uint256 receiver; uint256 makerAsset; uint256 takerAsset; uint256 makingAmount; uint256 takingAmount; uint256 makerTraits; }
+            function getMakingAmount(Order order, bytes extension, bytes32 orderHash, address taker, uint256 takingAmount, uint256 remainingMakingAmount, bytes extraData) external view returns (uint256)
+            function getTakingAmount(Order order, bytes extension, bytes32 orderHash, address taker, uint256 makingAmount, uint256 remainingMakingAmount, bytes extraData) external view returns (uint256)
+        ]"#
+    );
+}
@@ -0,0 +1,250 @@
+// Fork profile: which chain anvil forks from and how the local node is tuned
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{env, fs, str::FromStr};
+use eyre::{Result, eyre};
+
+/// Default BSC mainnet archive RPC used when no profile overrides it
+pub const DEFAULT_RPC_URL: &str = "https://api.zan.top/node/v1/bsc/mainnet/2d661fce966a44139a2d4c61d373851f";
+
+/// BSC mainnet chain id
+pub const BSC_CHAIN_ID: u64 = 56;
+
+/// Block to fork from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ForkBlock {
+    Latest,
+    Number(u64),
+}
+
+impl FromStr for ForkBlock {
+    type Err = eyre::Report;
+
+    /// Accepts "latest", a decimal block number or a 0x-prefixed hex block number
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("latest") {
+            return Ok(ForkBlock::Latest);
+        }
+        let number = match s.strip_prefix("0x") {
+            Some(hex_str) => u64::from_str_radix(hex_str, 16)?,
+            None => s.parse::<u64>()?,
+        };
+        Ok(ForkBlock::Number(number))
+    }
+}
+
+impl Serialize for ForkBlock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            ForkBlock::Latest => serializer.serialize_str("latest"),
+            ForkBlock::Number(n) => serializer.serialize_u64(*n),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ForkBlock {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(u64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(n) => Ok(ForkBlock::Number(n)),
+            Raw::Text(s) => ForkBlock::from_str(&s).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Everything `setup_blockchain` needs to spawn anvil
+///
+/// `rpc_url: None` spawns a plain local anvil with no fork, which needs no
+/// network access and can't take a `fork_block` number. When forking, `chain_id` is the chain the RPC is expected
+/// to report; for a local node it is passed to anvil as `--chain-id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForkProfile {
+    pub rpc_url: Option<String>,
+    pub chain_id: Option<u64>,
+    pub fork_block: Option<ForkBlock>,
+    pub block_time: Option<u64>,               // Seconds between mined blocks (auto-mine if unset)
+    pub gas_limit: Option<u64>,                // Block gas limit
+    pub base_fee: Option<u64>,                 // Initial base fee in wei
+    pub fork_retry_backoff: Option<u64>,       // Milliseconds between fork RPC retries
+    pub compute_units_per_second: Option<u64>, // Fork RPC rate limit
+}
+
+impl Default for ForkProfile {
+    fn default() -> Self {
+        Self::bsc_mainnet()
+    }
+}
+
+impl ForkProfile {
+    /// BSC mainnet fork through the default RPC
+    pub fn bsc_mainnet() -> Self {
+        Self {
+            rpc_url: Some(DEFAULT_RPC_URL.to_string()),
+            chain_id: Some(BSC_CHAIN_ID),
+            fork_block: None,
+            block_time: None,
+            gas_limit: None,
+            base_fee: None,
+            fork_retry_backoff: None,
+            compute_units_per_second: None,
+        }
+    }
+
+    /// Plain local anvil without a fork
+    pub fn local() -> Self {
+        Self {
+            rpc_url: None,
+            chain_id: None,
+            ..Self::bsc_mainnet()
+        }
+    }
+
+    /// Load a profile from a JSON file; missing fields fall back to the BSC defaults
+    pub fn load_from_file(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let profile: ForkProfile = serde_json::from_str(&content)?;
+        Ok(profile)
+    }
+
+    /// Resolve the profile from (lowest to highest precedence) the BSC defaults,
+    /// a JSON file given by `FORK_PROFILE` or `--fork-profile`, `FORK_*` env vars
+    /// and CLI flags; errors on a fork block without a fork RPC
+    pub fn resolve(args: &[String]) -> Result<Self> {
+        let profile_path = flag_value(args, "--fork-profile")
+            .or_else(|| env::var("FORK_PROFILE").ok());
+
+        let mut profile = match profile_path {
+            Some(path) => Self::load_from_file(&path)?,
+            None => Self::default(),
+        };
+        profile.apply_env()?;
+        profile.apply_args(args)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Override fields from `FORK_*` environment variables
+    ///
+    /// An empty `FORK_RPC_URL` (or `none`) selects a local anvil without a fork.
+    pub fn apply_env(&mut self) -> Result<()> {
+        if let Ok(url) = env::var("FORK_RPC_URL") {
+            self.set_rpc_url(&url);
+        }
+        if let Some(v) = env_value("FORK_CHAIN_ID")? { self.chain_id = Some(v); }
+        if let Ok(block) = env::var("FORK_BLOCK") {
+            self.fork_block = Some(block.parse()?);
+        }
+        if let Some(v) = env_value("FORK_BLOCK_TIME")? { self.block_time = Some(v); }
+        if let Some(v) = env_value("FORK_GAS_LIMIT")? { self.gas_limit = Some(v); }
+        if let Some(v) = env_value("FORK_BASE_FEE")? { self.base_fee = Some(v); }
+        if let Some(v) = env_value("FORK_RETRY_BACKOFF")? { self.fork_retry_backoff = Some(v); }
+        if let Some(v) = env_value("FORK_COMPUTE_UNITS_PER_SECOND")? { self.compute_units_per_second = Some(v); }
+        Ok(())
+    }
+
+    /// Override fields from CLI flags (`--fork-url`, `--no-fork`, `--chain-id`,
+    /// `--fork-block`, `--block-time`, `--gas-limit`, `--base-fee`,
+    /// `--fork-retry-backoff`, `--compute-units-per-second`)
+    pub fn apply_args(&mut self, args: &[String]) -> Result<()> {
+        if let Some(url) = flag_value(args, "--fork-url") {
+            self.set_rpc_url(&url);
+        }
+        if args.iter().any(|a| a == "--no-fork") {
+            self.rpc_url = None;
+        }
+        if let Some(v) = flag_number(args, "--chain-id")? { self.chain_id = Some(v); }
+        if let Some(block) = flag_value(args, "--fork-block") {
+            self.fork_block = Some(block.parse()?);
+        }
+        if let Some(v) = flag_number(args, "--block-time")? { self.block_time = Some(v); }
+        if let Some(v) = flag_number(args, "--gas-limit")? { self.gas_limit = Some(v); }
+        if let Some(v) = flag_number(args, "--base-fee")? { self.base_fee = Some(v); }
+        if let Some(v) = flag_number(args, "--fork-retry-backoff")? { self.fork_retry_backoff = Some(v); }
+        if let Some(v) = flag_number(args, "--compute-units-per-second")? { self.compute_units_per_second = Some(v); }
+        Ok(())
+    }
+
+    /// A local anvil starts at genesis, so it can't honor a fork block number
+    pub fn validate(&self) -> Result<()> {
+        if let (None, Some(ForkBlock::Number(block))) = (&self.rpc_url, self.fork_block) {
+            return Err(eyre!("Fork block {} needs a fork RPC, a local anvil without fork starts at block 0", block));
+        }
+        Ok(())
+    }
+
+    fn set_rpc_url(&mut self, url: &str) {
+        let url = url.trim();
+        self.rpc_url = if url.is_empty() || url.eq_ignore_ascii_case("none") {
+            None
+        } else {
+            Some(url.to_string())
+        };
+    }
+}
+
+/// Value following `flag` in `args`, also accepting `flag=value`
+pub fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    let prefix = format!("{}=", flag);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            return iter.next().cloned();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+fn flag_number(args: &[String], flag: &str) -> Result<Option<u64>> {
+    flag_value(args, flag)
+        .map(|v| v.parse::<u64>().map_err(|e| eyre!("Invalid value for {}: {}", flag, e)))
+        .transpose()
+}
+
+fn env_value(name: &str) -> Result<Option<u64>> {
+    env::var(name)
+        .ok()
+        .map(|v| v.parse::<u64>().map_err(|e| eyre!("Invalid value for {}: {}", name, e)))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn no_fork_flag_selects_local_mode() {
+        let mut profile = ForkProfile::default();
+        profile.apply_args(&args(&["--no-fork", "--chain-id", "31337"])).unwrap();
+        assert_eq!(profile.rpc_url, None);
+        assert_eq!(profile.chain_id, Some(31337));
+        assert!(profile.validate().is_ok());
+    }
+
+    #[test]
+    fn local_mode_rejects_a_fork_block() {
+        let mut profile = ForkProfile::local();
+        profile.apply_args(&args(&["--fork-block", "40000000"])).unwrap();
+        assert!(profile.validate().is_err());
+
+        profile.apply_args(&args(&["--fork-block", "latest"])).unwrap();
+        assert!(profile.validate().is_ok());
+
+        profile.apply_args(&args(&["--fork-url", "http://localhost:8545", "--fork-block", "0x10"])).unwrap();
+        assert_eq!(profile.fork_block, Some(ForkBlock::Number(16)));
+        assert!(profile.validate().is_ok());
+    }
+}
@@ -1,3 +1,4 @@
 // Configuration modules for Uniswap V2 demo
 
 pub mod simple_config;
+pub mod fork_profile;
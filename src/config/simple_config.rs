@@ -1,6 +1,8 @@
 // Simple configuration for Uniswap V2 swaps
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::str::FromStr;
 use eyre::Result;
 use ethers::types::U256;
 
@@ -28,6 +30,93 @@ pub struct TransactionInfo {
     pub is_complex: bool,
 }
 
+/// How a token balance is put into an account on the fork
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "strategy")]
+pub enum FundingStrategy {
+    /// Overwrite the balance slot found by `find_balance_slot`
+    #[default]
+    StorageSlot,
+    /// Transfer from a holder via `anvil_impersonateAccount`; the holder is
+    /// found from recent `Transfer` logs when not given
+    Impersonate {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        holder: Option<String>,
+    },
+    /// Overwrite the balance and adjust `totalSupply` to match (native asset: `anvil_setBalance`)
+    Deal,
+}
+
+/// How an account lets the 1inch router pull a token
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "strategy")]
+pub enum AllowanceStrategy {
+    /// Plain ERC20 `approve` to the router
+    #[default]
+    Approve,
+    /// Signed EIP-2612 permit, applied by the router (`permitAndCall` or MakerPermit)
+    Permit,
+    /// ERC20 approval to Permit2 plus a signed `PermitSingle` for the router; the router
+    /// then transfers through Permit2 (USE_PERMIT2)
+    Permit2,
+}
+
+impl FromStr for AllowanceStrategy {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "approve" => Ok(AllowanceStrategy::Approve),
+            "permit" => Ok(AllowanceStrategy::Permit),
+            "permit2" => Ok(AllowanceStrategy::Permit2),
+            other => Err(eyre::eyre!("Unknown allowance strategy '{}' (expected approve, permit or permit2)", other)),
+        }
+    }
+}
+
+/// Extra taker state a gated order needs before filling, e.g. the access token a resolver
+/// check reads; the default adds nothing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct TakerSetup {
+    /// Token address -> amount the taker must hold: base units (decimal or 0x hex), or whole
+    /// tokens with a decimal point, like "1.5", scaled by the token's decimals()
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hold_tokens: BTreeMap<String, String>,
+    /// How `hold_tokens` are put into the taker (defaults to StorageSlot)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub funding: Option<FundingStrategy>,
+    /// Account to fill from instead of the signer, impersonated on the fork
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker: Option<TakerAccount>,
+}
+
+/// Impersonated account a fill is sent from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum TakerAccount {
+    /// A fixed address, e.g. the resolver a private order was made for
+    Address { address: String },
+    /// An address ending in the order's MakerTraits allowedSender bytes; public orders keep the signer
+    AllowedSender,
+}
+
+/// Callback the router makes between the maker and taker transfers of a 1inch fill
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum TakerInteraction {
+    /// Batch taker receives the maker asset and swaps it on PancakeSwap V2 into the taking
+    /// amount for the taker, so the taker needs no balance of its own
+    PancakeV2FlashFill,
+    /// `target.takerInteraction(..., extra_data)`; the maker asset goes to `receiver` when set
+    Custom {
+        target: String,
+        #[serde(default)]
+        extra_data: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        receiver: Option<String>,
+    },
+}
+
 /// Simple swap configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwapConfig {
@@ -39,6 +128,8 @@ pub struct SwapConfig {
     pub fee: u32,                 // Fee in basis points
     #[serde(rename = "type")]
     pub pool_type: PoolType,      // Pool type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub funding: Option<FundingStrategy>, // How token1 is funded (defaults to StorageSlot)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transaction_info: Option<TransactionInfo>, // Debug info
 }
@@ -56,10 +147,26 @@ pub struct OneInchOrder {
     pub maker_traits: String,            // Maker traits
     pub r: String,                       // Signature r component
     pub vs: String,                      // Signature vs component
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,       // Raw maker signature for ERC-1271 makers (defaults to r ++ vs)
     pub amount: String,                  // Amount to fill
     pub taker_traits: String,            // Taker traits (can be 0)
     pub expected_amount_out: String,     // Expected amount out from fill
     pub expected_remaining_amount: String, // Expected remaining amount after fill
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub funding: Option<FundingStrategy>, // How the taker asset is funded (defaults to StorageSlot)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_hash: Option<String>,       // Expected EIP-712 order hash, checked before filling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker_interaction: Option<TakerInteraction>, // Taker callback for fillOrderArgs (e.g. a flash fill)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker_allowance: Option<AllowanceStrategy>, // How the taker allows the router (defaults to Approve)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pay_native: bool,                 // Pay a WBNB taker asset in BNB via msg.value
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unwrap_native: bool,              // Receive a WBNB maker asset as BNB (taker UNWRAP_WETH)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker_setup: Option<TakerSetup>,  // Access the taker needs for gated orders (defaults to none)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transaction_info: Option<TransactionInfo>, // Debug info
 }
@@ -113,7 +220,7 @@ impl Config {
 
     /// Get all order configs
     pub fn get_all_orders(&self) -> &[OneInchOrder] {
-        self.orders.as_ref().map(|o| o.as_slice()).unwrap_or(&[])
+        self.orders.as_deref().unwrap_or(&[])
     }
 
     /// Count of order configs
@@ -206,7 +313,7 @@ impl SwapConfig {
 }
 
 /// Comparison result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComparisonResult {
     pub expected: f64,
     pub actual: f64,
@@ -225,6 +332,10 @@ pub struct MevConfig {
     pub block_number: u64,
     pub taker_traits: String,
     pub one_inch_orders: Vec<MevOneInchOrder>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub funding: BTreeMap<String, FundingStrategy>, // Per-token funding strategy keyed by token address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker_setup: Option<TakerSetup>, // Applied to every order before filling
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -232,6 +343,8 @@ pub struct MevOneInchOrder {
     pub amount_in: String,   // hex format
     pub amount_out: String,  // hex format
     pub order: MevOrder,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>, // Overrides MevConfig.block_number for this order
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -245,37 +358,54 @@ pub struct MevOrder {
     pub making_amount: String,
     pub remaining_making_amount: String,
     pub taking_amount: String,
-    pub maker_traits: String,  // Changed to hex string
-    pub extension: String,     // Extension field
+    pub maker_traits: String,  // Hex string, decode with one_inch::maker_traits::MakerTraits
+    pub extension: String,     // Hex extension, parse with one_inch::extension::OrderExtension
     pub signature: String,     // Full signature hex string
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct MevMakerTraits {
-    pub no_partial_fills: bool,
-    pub allow_multiple_fills: bool,
-}
-
 impl MevConfig {
     pub fn load_from_file(path: &str) -> Result<Self> {
         let content = fs::read_to_string(path)?;
         let config: MevConfig = serde_json::from_str(&content)?;
         Ok(config)
     }
+
+    /// Funding strategy configured for `token` (address compared case-insensitively)
+    pub fn funding_for(&self, token: &str) -> FundingStrategy {
+        self.funding
+            .iter()
+            .find(|(address, _)| address.eq_ignore_ascii_case(token))
+            .map(|(_, strategy)| strategy.clone())
+            .unwrap_or_default()
+    }
 }
 
 impl MevOneInchOrder {
+    /// Block the order was filled in, falling back to the config-wide block
+    pub fn transaction_block(&self, default_block: u64) -> u64 {
+        self.block_number.unwrap_or(default_block)
+    }
+
+    /// Block to fork for this order (block - 1 for pre-transaction state)
+    pub fn fork_block(&self, default_block: u64) -> u64 {
+        self.transaction_block(default_block).saturating_sub(1)
+    }
+
     /// Convert hex string to decimal string
     fn hex_to_decimal(hex_str: &str) -> Result<String> {
         // If it's already decimal, return as is
         if !hex_str.starts_with("0x") {
             return Ok(hex_str.to_string());
         }
+        Ok(Self::parse_amount(hex_str)?.to_string())
+    }
 
-        let hex_clean = hex_str.trim_start_matches("0x");
-        // Use U256 for large numbers
-        let decimal = U256::from_str_radix(hex_clean, 16)?;
-        Ok(decimal.to_string())
+    /// Amount field of captured data, 0x hex or decimal
+    pub fn parse_amount(value: &str) -> Result<U256> {
+        Ok(match value.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16)?,
+            None => U256::from_dec_str(value)?,
+        })
     }
 
     /// Convert MEV order to standard OneInchOrder format
@@ -286,6 +416,13 @@ impl MevOneInchOrder {
         let amount = Self::hex_to_decimal(&self.amount_in)?;
         let expected_amount_out = Self::hex_to_decimal(&self.amount_out)?;
 
+        // Only ECDSA signatures split into r and vs; contract makers sign in their own format,
+        // which only `signature` carries
+        let (r, vs) = match self.order.signature.trim_start_matches("0x").len() {
+            130 => (Self::extract_r_from_signature(&self.order.signature)?, Self::extract_vs_from_signature(&self.order.signature)?),
+            _ => (format!("0x{}", "0".repeat(64)), format!("0x{}", "0".repeat(64))),
+        };
+
         // Convert addresses to packed format (simplified - you may need more complex logic)
         let maker_asset = Self::address_to_packed(&self.order.maker_asset)?;
         let taker_asset = Self::address_to_packed(&self.order.taker_asset)?;
@@ -305,12 +442,20 @@ impl MevOneInchOrder {
             making_amount,
             taking_amount,
             maker_traits: Self::hex_to_decimal(&self.order.maker_traits)?,
-            r: Self::extract_r_from_signature(&self.order.signature)?,
-            vs: Self::extract_vs_from_signature(&self.order.signature)?,
+            r,
+            vs,
+            signature: Some(self.order.signature.clone()),
             amount,
             taker_traits: Self::hex_to_decimal(taker_traits)?,
             expected_amount_out,
             expected_remaining_amount: Self::hex_to_decimal(&self.order.remaining_making_amount)?,
+            funding: None,
+            order_hash: Some(self.order.order_hash.clone()),
+            taker_interaction: None,
+            taker_allowance: None,
+            pay_native: false,
+            unwrap_native: false,
+            taker_setup: None,
             transaction_info: Some(TransactionInfo {
                 hash: self.order.order_hash.clone(),
                 method: "MEV Order".to_string(),
@@ -0,0 +1,229 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::{
+    abi::Detokenize,
+    contract::ContractCall,
+    providers::Middleware,
+    types::{Address, H256, I256, Log, U256, U64},
+};
+use serde::{Deserialize, Serialize};
+use eyre::Result;
+use tracing::info;
+
+use crate::anvil_setup::{SignerClient, get_token_balance};
+
+/// Whether simulations only `eth_call` or broadcast and mine on the fork
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionMode {
+    /// Read the result with `eth_call`; nothing is mined
+    #[default]
+    Call,
+    /// Send the transaction, wait for the receipt and record gas, logs and balances
+    Send,
+}
+
+impl FromStr for ExecutionMode {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "call" => Ok(ExecutionMode::Call),
+            "send" => Ok(ExecutionMode::Send),
+            other => Err(eyre::eyre!("Unknown execution mode '{}' (expected call or send)", other)),
+        }
+    }
+}
+
+impl fmt::Display for ExecutionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionMode::Call => write!(f, "call"),
+            ExecutionMode::Send => write!(f, "send"),
+        }
+    }
+}
+
+/// A (role, account, token) balance to record around a sent transaction;
+/// `Address::zero()` as token means the native balance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceWatch {
+    pub label: String,
+    pub account: Address,
+    pub token: Address,
+}
+
+impl BalanceWatch {
+    pub fn new(label: &str, account: Address, token: Address) -> Self {
+        Self { label: label.to_string(), account, token }
+    }
+}
+
+/// Balance of a watched account before and after execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceChange {
+    pub label: String,
+    pub account: Address,
+    pub token: Address,
+    pub before: U256,
+    pub after: U256,
+}
+
+impl BalanceChange {
+    /// Signed change (after - before)
+    pub fn delta(&self) -> I256 {
+        I256::from_raw(self.after).overflowing_sub(I256::from_raw(self.before)).0
+    }
+}
+
+/// What a mined transaction did on the fork
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReceipt {
+    pub tx_hash: H256,
+    pub block_number: Option<U64>,
+    pub gas_used: U256,
+    pub effective_gas_price: U256,
+    pub logs: Vec<Log>,
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+impl ExecutionReceipt {
+    /// Log gas, events and balance deltas
+    pub fn log_summary(&self) {
+        info!("🧾 Transaction 0x{} mined in block {:?}", hex::encode(self.tx_hash), self.block_number);
+        info!("  Gas Used: {}", self.gas_used);
+        info!("  Effective Gas Price: {} wei", self.effective_gas_price);
+        info!("  Logs Emitted: {}", self.logs.len());
+        for change in &self.balance_changes {
+            info!("  {} {:?} balance of {:?}: {} -> {} ({})",
+                  change.label, change.token, change.account, change.before, change.after, change.delta());
+        }
+    }
+
+    /// Native balance deltas, with the gas `sender` paid for this transaction added back
+    pub fn native_deltas(&self, sender: Address) -> Vec<(&BalanceChange, I256)> {
+        let gas_cost = I256::from_raw(self.gas_used * self.effective_gas_price);
+        self.balance_changes.iter()
+            .filter(|change| change.token == Address::zero())
+            .map(|change| match change.account == sender {
+                true => (change, change.delta() + gas_cost),
+                false => (change, change.delta()),
+            })
+            .collect()
+    }
+}
+
+/// A sent transaction that was mined but reverted, with what it cost
+#[derive(Debug, Clone)]
+pub struct RevertedTransaction(pub ExecutionReceipt);
+
+impl fmt::Display for RevertedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Transaction 0x{} reverted on the fork (gas used {})", hex::encode(self.0.tx_hash), self.0.gas_used)
+    }
+}
+
+impl std::error::Error for RevertedTransaction {}
+
+/// Receipt of a mined revert carried by `error`, if it is one
+pub fn reverted_receipt(error: &eyre::Report) -> Option<ExecutionReceipt> {
+    error.downcast_ref::<RevertedTransaction>().map(|reverted| reverted.0.clone())
+}
+
+/// Run `call` according to `mode`
+///
+/// The decoded return value always comes from an `eth_call` against the
+/// pre-transaction state; in `Send` mode the call is then broadcast and its
+/// receipt and the `watches` balances are returned as well. A mined revert is
+/// a `RevertedTransaction` error holding the receipt.
+pub async fn execute_call<D: Detokenize>(
+    client: &Arc<SignerClient>,
+    call: ContractCall<SignerClient, D>,
+    mode: ExecutionMode,
+    watches: &[BalanceWatch],
+) -> Result<(D, Option<ExecutionReceipt>)> {
+    let output = call.call().await?;
+    if mode == ExecutionMode::Call {
+        return Ok((output, None));
+    }
+
+    let before = read_balances(client, watches).await?;
+
+    let receipt = call
+        .send()
+        .await?
+        .await?
+        .ok_or_else(|| eyre::eyre!("Transaction dropped from the fork mempool"))?;
+
+    let reverted = receipt.status != Some(U64::one());
+    let after = read_balances(client, watches).await?;
+    let balance_changes = watches
+        .iter()
+        .zip(before.into_iter().zip(after))
+        .map(|(watch, (before, after))| BalanceChange {
+            label: watch.label.clone(),
+            account: watch.account,
+            token: watch.token,
+            before,
+            after,
+        })
+        .collect();
+
+    let report = ExecutionReceipt {
+        tx_hash: receipt.transaction_hash,
+        block_number: receipt.block_number,
+        gas_used: receipt.gas_used.unwrap_or_default(),
+        effective_gas_price: receipt.effective_gas_price.unwrap_or_default(),
+        logs: receipt.logs,
+        balance_changes,
+    };
+    report.log_summary();
+
+    if reverted {
+        return Err(RevertedTransaction(report).into());
+    }
+    Ok((output, Some(report)))
+}
+
+/// Current balances for every watch, in order
+pub async fn read_balances(client: &Arc<SignerClient>, watches: &[BalanceWatch]) -> Result<Vec<U256>> {
+    let mut balances = Vec::with_capacity(watches.len());
+    for watch in watches {
+        let balance = if watch.token == Address::zero() {
+            client.get_balance(watch.account, None).await?
+        } else {
+            get_token_balance(client, watch.token, watch.account).await?
+        };
+        balances.push(balance);
+    }
+    Ok(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{SimulationInputs, SimulationOutcome};
+
+    #[test]
+    fn mined_revert_keeps_the_receipt() {
+        let receipt = ExecutionReceipt {
+            tx_hash: H256::repeat_byte(0xab),
+            block_number: Some(U64::from(7)),
+            gas_used: U256::from(51_234),
+            effective_gas_price: U256::from(1_000_000_000u64),
+            logs: Vec::new(),
+            balance_changes: Vec::new(),
+        };
+        let error: eyre::Report = RevertedTransaction(receipt).into();
+        assert_eq!(reverted_receipt(&error).map(|r| r.gas_used), Some(U256::from(51_234)));
+        assert!(reverted_receipt(&eyre::eyre!("call failed")).is_none());
+
+        let inputs = SimulationInputs { token_in: Address::zero(), token_out: Address::zero(), amount: U256::one(), expected_amount_out: None };
+        let mut outcome = SimulationOutcome::new("1inch", ExecutionMode::Send, inputs);
+        outcome.revert(error);
+        assert!(!outcome.is_success());
+        assert_eq!(outcome.gas_used(), Some(U256::from(51_234)));
+        assert!(outcome.revert_reason.unwrap().contains("reverted on the fork (gas used 51234)"));
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Mutex, OwnedMutexGuard, Semaphore};
+use tokio::task::JoinSet;
+use eyre::Result;
+use tracing::{info, error};
+
+use crate::anvil_setup::SignerClient;
+use crate::config::fork_profile::{ForkProfile, ForkBlock};
+use crate::fork_session::ForkSession;
+
+/// Forks spawned for one fork block, handed out round-robin
+struct BlockForks {
+    sessions: Vec<Arc<Mutex<ForkSession>>>,
+    next: AtomicUsize,
+}
+
+/// Pool of anvil forks for running simulations concurrently
+///
+/// One group of `instances_per_block` forks is spawned per distinct fork block.
+/// A lease gives exclusive use of a fork until dropped, so snapshot/revert
+/// isolation holds even while other forks are busy.
+pub struct ForkPool {
+    forks: BTreeMap<ForkBlock, BlockForks>,
+}
+
+/// Exclusive use of one pooled fork
+pub struct ForkLease {
+    session: OwnedMutexGuard<ForkSession>,
+}
+
+impl ForkLease {
+    /// Signer client connected to the leased fork
+    pub fn client(&self) -> Arc<SignerClient> {
+        self.session.client()
+    }
+
+    /// The leased fork session
+    pub fn session(&self) -> &ForkSession {
+        &self.session
+    }
+}
+
+impl ForkPool {
+    /// Spawn `instances_per_block` forks of `profile` for every distinct block in `blocks`
+    pub async fn spawn(profile: &ForkProfile, blocks: &[ForkBlock], instances_per_block: usize) -> Result<Self> {
+        let instances_per_block = instances_per_block.max(1);
+        let mut forks = BTreeMap::new();
+
+        for block in blocks {
+            if forks.contains_key(block) {
+                continue;
+            }
+
+            let block_profile = ForkProfile {
+                fork_block: Some(*block),
+                ..profile.clone()
+            };
+
+            let mut sessions = Vec::with_capacity(instances_per_block);
+            for _ in 0..instances_per_block {
+                let session = ForkSession::spawn(&block_profile).await?;
+                sessions.push(Arc::new(Mutex::new(session)));
+            }
+            info!("🍴 Spawned {} fork(s) at block {:?}", instances_per_block, block);
+
+            forks.insert(*block, BlockForks {
+                sessions,
+                next: AtomicUsize::new(0),
+            });
+        }
+
+        Ok(Self { forks })
+    }
+
+    /// Total number of anvil instances in the pool
+    pub fn size(&self) -> usize {
+        self.forks.values().map(|f| f.sessions.len()).sum()
+    }
+
+    /// Lease a fork at `block`, preferring an idle one and otherwise waiting round-robin
+    pub async fn lease(&self, block: ForkBlock) -> Result<ForkLease> {
+        let block_forks = self.forks.get(&block)
+            .ok_or_else(|| eyre::eyre!("No fork spawned for block {:?}", block))?;
+
+        for session in &block_forks.sessions {
+            if let Ok(guard) = session.clone().try_lock_owned() {
+                return Ok(ForkLease { session: guard });
+            }
+        }
+
+        let index = block_forks.next.fetch_add(1, Ordering::Relaxed) % block_forks.sessions.len();
+        let guard = block_forks.sessions[index].clone().lock_owned().await;
+        Ok(ForkLease { session: guard })
+    }
+
+    /// Run `f` for every `(block, job)` with at most `parallelism` jobs in flight
+    ///
+    /// Each job runs isolated on a leased fork at its block. Results are returned
+    /// in input order regardless of completion order.
+    pub async fn run_batch<J, R, F, Fut>(
+        self: &Arc<Self>,
+        jobs: Vec<(ForkBlock, J)>,
+        parallelism: usize,
+        f: F,
+    ) -> Vec<Result<R>>
+    where
+        J: Send + 'static,
+        R: Send + 'static,
+        F: Fn(Arc<SignerClient>, J) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+    {
+        let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+        let f = Arc::new(f);
+        let job_count = jobs.len();
+        let mut tasks = JoinSet::new();
+
+        for (index, (block, job)) in jobs.into_iter().enumerate() {
+            let pool = self.clone();
+            let semaphore = semaphore.clone();
+            let f = f.clone();
+
+            tasks.spawn(async move {
+                let result = async {
+                    let _permit = semaphore.acquire_owned().await?;
+                    let lease = pool.lease(block).await?;
+                    lease.session().with_isolated(|client| f(client, job)).await
+                }.await;
+                (index, result)
+            });
+        }
+
+        let mut results: Vec<Option<Result<R>>> = (0..job_count).map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = Some(result),
+                Err(e) => error!("Simulation task panicked: {}", e),
+            }
+        }
+
+        results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(eyre::eyre!("Simulation task did not complete"))))
+            .collect()
+    }
+}
@@ -0,0 +1,93 @@
+use std::future::Future;
+use std::sync::Arc;
+use ethers::{
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::U256,
+    utils::AnvilInstance,
+};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::anvil_setup::{setup_blockchain, SignerClient};
+use crate::config::fork_profile::ForkProfile;
+
+/// Long-lived anvil fork that simulations share through `evm_snapshot`/`evm_revert`
+///
+/// Snapshots are node-wide, so callers must not run isolated simulations on the
+/// same session concurrently; use one session per task (see `ForkPool`).
+pub struct ForkSession {
+    anvil: AnvilInstance,
+    client: Arc<SignerClient>,
+    profile: ForkProfile,
+}
+
+impl ForkSession {
+    /// Spawn anvil for `profile` and connect the signer client
+    pub async fn spawn(profile: &ForkProfile) -> Result<Self> {
+        let (anvil, client) = setup_blockchain(profile).await?;
+        Ok(Self {
+            anvil,
+            client,
+            profile: profile.clone(),
+        })
+    }
+
+    /// Signer client connected to this fork
+    pub fn client(&self) -> Arc<SignerClient> {
+        self.client.clone()
+    }
+
+    /// Profile the fork was spawned with
+    pub fn profile(&self) -> &ForkProfile {
+        &self.profile
+    }
+
+    /// Anvil dev account `index` (0 is the taker behind `client`), bound to the fork's chain id
+    pub fn wallet(&self, index: usize) -> Result<LocalWallet> {
+        let key = self.anvil.keys().get(index)
+            .ok_or_else(|| eyre::eyre!("Anvil has {} dev accounts, no index {}", self.anvil.keys().len(), index))?;
+        Ok(LocalWallet::from(key.clone()).with_chain_id(self.client.signer().chain_id()))
+    }
+
+    /// HTTP endpoint of the anvil node
+    pub fn endpoint(&self) -> String {
+        self.anvil.endpoint()
+    }
+
+    /// Take an `evm_snapshot` of the current fork state
+    pub async fn snapshot(&self) -> Result<U256> {
+        let id: U256 = self.client.provider().request("evm_snapshot", ()).await?;
+        Ok(id)
+    }
+
+    /// Restore the state captured by `snapshot`
+    ///
+    /// Anvil drops the snapshot (and every later one) on revert, so each id can only be used once.
+    pub async fn revert(&self, id: U256) -> Result<()> {
+        let reverted: bool = self.client.provider().request("evm_revert", [id]).await?;
+        if !reverted {
+            return Err(eyre::eyre!("evm_revert to snapshot {} failed", id));
+        }
+        Ok(())
+    }
+
+    /// Run `f` against the fork and roll back every state change it made,
+    /// whether it succeeded or not
+    pub async fn with_isolated<F, Fut, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(Arc<SignerClient>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let snapshot_id = self.snapshot().await?;
+        let result = f(self.client()).await;
+
+        if let Err(e) = self.revert(snapshot_id).await {
+            warn!("Failed to revert fork to snapshot {}: {}", snapshot_id, e);
+            return Err(e);
+        }
+        info!("Fork state reverted to snapshot {}", snapshot_id);
+
+        result
+    }
+}
@@ -0,0 +1,189 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::{
+    providers::Middleware,
+    types::{Address, BlockNumber, Filter, H256, U256, U64},
+};
+use eyre::Result;
+use tracing::{debug, info, warn};
+
+use crate::anvil_setup::{SignerClient, get_token_balance, set_token_balance_anvil};
+use crate::balance_slot::{find_total_supply_key, set_storage_at};
+use crate::bindings::erc20::IERC20;
+use crate::config::simple_config::FundingStrategy;
+
+// Transfer log scan for holder discovery: windows of this many blocks, up to the depth
+const HOLDER_SCAN_WINDOW: u64 = 500;
+const HOLDER_SCAN_DEPTH: u64 = 5000;
+
+/// Native BNB placeholder address used by aggregators
+const NATIVE_PLACEHOLDER: &str = "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE";
+
+/// Give `account` a balance of `amount` of `token` using `strategy`
+pub async fn fund_account(
+    client: &Arc<SignerClient>,
+    token: Address,
+    account: Address,
+    amount: U256,
+    strategy: &FundingStrategy,
+) -> Result<()> {
+    match strategy {
+        FundingStrategy::StorageSlot => set_token_balance_anvil(client, token, account, amount).await,
+        FundingStrategy::Impersonate { holder } => {
+            let holder = match holder {
+                Some(holder) => Address::from_str(holder)?,
+                None => find_token_holder(client, token, amount).await?,
+            };
+            fund_from_holder(client, token, holder, account, amount).await
+        }
+        FundingStrategy::Deal => deal(client, token, account, amount).await,
+    }
+}
+
+/// Whether `token` denotes the native asset rather than an ERC20
+pub fn is_native_asset(token: Address) -> bool {
+    token == Address::zero() || Address::from_str(NATIVE_PLACEHOLDER).map(|n| n == token).unwrap_or(false)
+}
+
+/// Top `account` up to `amount` by transferring from an impersonated `holder`
+///
+/// Fee-on-transfer and reflection tokens can deliver less than was sent; the
+/// shortfall is logged and the caller sees the real balance.
+pub async fn fund_from_holder(
+    client: &Arc<SignerClient>,
+    token: Address,
+    holder: Address,
+    account: Address,
+    amount: U256,
+) -> Result<()> {
+    let current = get_token_balance(client, token, account).await?;
+    if current >= amount {
+        return Ok(());
+    }
+    let needed = amount - current;
+
+    let holder_balance = get_token_balance(client, token, holder).await?;
+    if holder_balance < needed {
+        return Err(eyre::eyre!("Holder {:?} has {} of {:?}, need {}", holder, holder_balance, token, needed));
+    }
+
+    info!("🐋 Transferring {} of {:?} from impersonated holder {:?}", needed, token, holder);
+    let provider = client.provider();
+    provider.request::<_, ()>("anvil_impersonateAccount", [holder]).await?;
+
+    // Make sure the holder can pay for gas
+    let gas_money = U256::exp10(18);
+    if client.get_balance(holder, None).await? < gas_money {
+        provider.request::<_, ()>("anvil_setBalance", (holder, format!("0x{:x}", gas_money))).await?;
+    }
+
+    // Send unsigned from the holder through the raw provider; anvil accepts it while impersonating
+    let token_contract = IERC20::new(token, Arc::new(provider.clone()));
+    let transfer = token_contract.transfer(account, needed).from(holder);
+    let receipt = match transfer.send().await {
+        Ok(pending) => pending.await.map_err(eyre::Report::from),
+        Err(e) => Err(eyre::Report::from(e)),
+    };
+    provider.request::<_, ()>("anvil_stopImpersonatingAccount", [holder]).await?;
+
+    let receipt = receipt?.ok_or_else(|| eyre::eyre!("Transfer from holder was dropped"))?;
+    if receipt.status != Some(U64::one()) {
+        return Err(eyre::eyre!("Transfer from holder {:?} reverted", holder));
+    }
+
+    let received = get_token_balance(client, token, account).await?;
+    if received < amount {
+        warn!("⚠️  {:?} balance is {} after impersonated transfer (wanted {}), token likely takes a transfer fee", token, received, amount);
+    }
+    Ok(())
+}
+
+/// Find an externally owned account holding at least `amount` of `token` among recent
+/// `Transfer` recipients
+pub async fn find_token_holder(client: &Arc<SignerClient>, token: Address, amount: U256) -> Result<Address> {
+    let transfer_topic = H256::from(ethers::utils::keccak256("Transfer(address,address,uint256)"));
+    let head = client.get_block_number().await?.as_u64();
+    let mut checked: Vec<Address> = Vec::new();
+
+    let mut to_block = head;
+    while head - to_block < HOLDER_SCAN_DEPTH {
+        let from_block = to_block.saturating_sub(HOLDER_SCAN_WINDOW - 1);
+        let filter = Filter::new()
+            .address(token)
+            .topic0(transfer_topic)
+            .from_block(BlockNumber::Number(from_block.into()))
+            .to_block(BlockNumber::Number(to_block.into()));
+
+        let logs = client.get_logs(&filter).await?;
+        for log in logs.iter().rev() {
+            let Some(to_topic) = log.topics.get(2) else { continue };
+            let candidate = Address::from(*to_topic);
+            if candidate == Address::zero() || candidate == token || checked.contains(&candidate) {
+                continue;
+            }
+            checked.push(candidate);
+
+            if get_token_balance(client, token, candidate).await? < amount {
+                continue;
+            }
+            // Pulling tokens out of a pair or vault desyncs its reserves and skews the swap
+            // being simulated, so only externally owned accounts are used
+            if !is_externally_owned(&client.get_code(candidate, None).await?) {
+                debug!("Skipping holder {:?}, it is a contract", candidate);
+                continue;
+            }
+            info!("🐋 Found holder {:?} for {:?}", candidate, token);
+            return Ok(candidate);
+        }
+
+        if from_block == 0 {
+            break;
+        }
+        to_block = from_block - 1;
+    }
+
+    Err(eyre::eyre!("No externally owned holder with {} of {:?} in the last {} blocks", amount, token, HOLDER_SCAN_DEPTH))
+}
+
+/// No code, or only an EIP-7702 delegation designator (`0xef0100 || address`)
+fn is_externally_owned(code: &[u8]) -> bool {
+    code.is_empty() || (code.len() == 23 && code.starts_with(&[0xef, 0x01, 0x00]))
+}
+
+/// Set the balance and keep `totalSupply` consistent, like forge-std `deal(token, to, amount, true)`
+pub async fn deal(client: &Arc<SignerClient>, token: Address, account: Address, amount: U256) -> Result<()> {
+    if is_native_asset(token) {
+        client.provider().request::<_, ()>("anvil_setBalance", (account, format!("0x{:x}", amount))).await?;
+        return Ok(());
+    }
+
+    let previous = get_token_balance(client, token, account).await?;
+    set_token_balance_anvil(client, token, account, amount).await?;
+
+    let supply_key = find_total_supply_key(client, token).await?;
+    let supply = U256::from_big_endian(client.get_storage_at(token, supply_key, None).await?.as_bytes());
+    let new_supply = if amount >= previous {
+        supply.saturating_add(amount - previous)
+    } else {
+        supply.saturating_sub(previous - amount)
+    };
+
+    let mut value = [0u8; 32];
+    new_supply.to_big_endian(&mut value);
+    set_storage_at(client, token, supply_key, H256::from(value)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holders_must_be_externally_owned() {
+        assert!(is_externally_owned(&[]));
+        let delegated = [[0xef, 0x01, 0x00].as_slice(), Address::repeat_byte(0x11).as_bytes()].concat();
+        assert!(is_externally_owned(&delegated));
+        // PancakeSwap pairs and other contracts are skipped
+        assert!(!is_externally_owned(&[0x60, 0x80, 0x60, 0x40, 0x52]));
+        assert!(!is_externally_owned(&delegated[..22]));
+    }
+}
@@ -3,5 +3,16 @@
 
 pub mod config;
 pub mod anvil_setup;
+pub mod balance_slot;
+pub mod batch_taker;
+pub mod execution;
+pub mod revert;
+pub mod simulation;
+pub mod report;
+pub mod bindings;
+pub mod funding;
+pub mod fork_session;
+pub mod fork_pool;
 pub mod one_inch;
 pub mod pancake_v2;
+pub mod uniswap_v3;
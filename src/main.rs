@@ -1,17 +1,18 @@
 use eyre::{Result, eyre};
-use tracing::{info,  error};
+use tracing::{info, warn, error};
 
 // Import modules
-use crate::config::simple_config::{Config, MevConfig};
-use crate::anvil_setup::{setup_blockchain};
-use crate::one_inch::{fill_order, fill_order_args};
 use std::sync::Arc;
-
-mod config;
-mod anvil_setup;
-mod one_inch;
-mod pancake_v2;
-mod uniswap_v3;
+use pancakeswap_v2_router_demo::config::simple_config::{Config, MevConfig, MevOneInchOrder, FundingStrategy, TakerInteraction, AllowanceStrategy, TakerSetup};
+use pancakeswap_v2_router_demo::config::fork_profile::{ForkProfile, ForkBlock, flag_value};
+use pancakeswap_v2_router_demo::fork_pool::ForkPool;
+use pancakeswap_v2_router_demo::execution::ExecutionMode;
+use pancakeswap_v2_router_demo::simulation::log_summary_table;
+use pancakeswap_v2_router_demo::report::{ReportRow, SimulationReport};
+use pancakeswap_v2_router_demo::one_inch::fill_order_args;
+use pancakeswap_v2_router_demo::one_inch::max_fill::find_max_fillable;
+use pancakeswap_v2_router_demo::one_inch::batch::fill_orders_batch;
+use pancakeswap_v2_router_demo::one_inch::order_builder::fill_self_order;
 
 
 #[tokio::main]
@@ -43,33 +44,182 @@ async fn main() -> Result<()> {
         orders: None,
     };
 
-    // Setup blockchain connection
-    let (_anvil, client) = setup_blockchain(&dummy_config).await?;
+    // Fork profile from --fork-profile / FORK_* env / CLI flags
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let fork_profile = ForkProfile::resolve(&args)?;
+    let parallelism = flag_number(&args, "--parallelism")?.unwrap_or(1);
+    let forks_per_block = flag_number(&args, "--forks-per-block")?.unwrap_or(1);
+    let mode: ExecutionMode = flag_value(&args, "--mode").map(|m| m.parse()).transpose()?.unwrap_or_default();
+    let report_path = flag_value(&args, "--report");
+    let search_max_fill = args.iter().any(|a| a == "--max-fill");
+    let batch = args.iter().any(|a| a == "--batch");
+    let self_order = args.iter().any(|a| a == "--self-order");
+    let flash_fill = args.iter().any(|a| a == "--flash-fill");
+    let pay_native = args.iter().any(|a| a == "--pay-native");
+    let unwrap_native = args.iter().any(|a| a == "--unwrap-native");
+    let taker_allowance: Option<AllowanceStrategy> = flag_value(&args, "--taker-allowance").map(|a| a.parse()).transpose()?;
+
+    // Each order forks at its own pre-transaction block unless the profile pins one; a local
+    // anvil without fork only has its latest block
+    let order_block = |mev_order: &MevOneInchOrder| match fork_profile.fork_block {
+        _ if fork_profile.rpc_url.is_none() => ForkBlock::Latest,
+        Some(block) => block,
+        None => ForkBlock::Number(mev_order.fork_block(dummy_config.get_transaction_block())),
+    };
+    let blocks: Vec<ForkBlock> = mev_config.one_inch_orders.iter().map(order_block).collect();
+
+    // Setup blockchain connections; every simulation runs on a snapshot of a pooled fork
+    let pool = Arc::new(ForkPool::spawn(&fork_profile, &blocks, forks_per_block).await?);
+    info!("Fork pool ready: {} instance(s), parallelism {}, mode {}", pool.size(), parallelism, mode);
 
     // Execute swap simulation for all configs (commented out for now)
     // for (i, swap_config) in config.get_all_swaps().iter().enumerate() {
     //     info!("🚀 Testing Swap Config #{}: {}", i + 1, swap_config.pool_type.display_name());
-    //     match execute_swap(swap_config, &client).await {
+    //     let lease = pool.lease(ForkBlock::Number(config.get_fork_block())).await?;
+    //     match lease.session().with_isolated(|client| async move { execute_swap(swap_config, &client, mode).await }).await {
     //         Ok(_) => info!("Swap #{} completed successfully", i + 1),
     //         Err(e) => error!("Swap #{} failed: {}", i + 1, e),
     //     }
     //     info!("");
     // }
 
+    // Access the taker needs for gated orders: the config's taker setup plus
+    // --access-token token[:amount] (one whole token by default); nothing is funded otherwise
+    let mut taker_setup = mev_config.taker_setup.clone();
+    if let Some(flag) = flag_value(&args, "--access-token") {
+        let (token, amount) = flag.split_once(':').unwrap_or((&flag, "1.0"));
+        taker_setup.get_or_insert_with(TakerSetup::default).hold_tokens.insert(token.to_string(), amount.to_string());
+    }
+
     // Execute MEV orders
-    for (mev_order_index, mev_order) in mev_config.one_inch_orders.iter().enumerate() {
-        match mev_order.to_standard_order(&mev_config.taker_traits) {
-            Ok(order_config) => {
-                match fill_order_args(&order_config, &mev_order.order.extension, &client).await {
-                    Ok(_) => info!("MEV Order #{} completed successfully", mev_order_index + 1),
-                    Err(e) => error!("MEV Order #{} failed: {}", mev_order_index + 1, e),
+    let taker_traits = mev_config.taker_traits.clone();
+    let jobs: Vec<(ForkBlock, (MevOneInchOrder, FundingStrategy))> = blocks.iter().copied()
+        .zip(mev_config.one_inch_orders.iter().map(|o| (o.clone(), mev_config.funding_for(&o.order.taker_asset))))
+        .collect();
+
+    if self_order {
+        // Dev account 1 makes an order on the first captured order's pair and the taker fills it
+        let template = mev_config.one_inch_orders[0].clone();
+        let maker_funding = mev_config.funding_for(&template.order.maker_asset);
+        let taker_funding = mev_config.funding_for(&template.order.taker_asset);
+        let lease = pool.lease(blocks[0]).await?;
+        let maker = lease.session().wallet(1)?;
+        let result = lease.session().with_isolated(|client| async move {
+            fill_self_order(&client, &maker, &template, &maker_funding, &taker_funding, mode).await
+        }).await;
+        match result {
+            Ok(outcome) if outcome.is_success() => info!("Self-signed order filled"),
+            Ok(outcome) => error!("Self-signed order reverted: {}", outcome.revert_reason.as_deref().unwrap_or_default()),
+            Err(e) => error!("Self-signed order failed: {}", e),
+        }
+    }
+
+    if search_max_fill {
+        let taker_traits = taker_traits.clone();
+        let taker_setup = taker_setup.clone();
+        let searches = pool.run_batch(jobs.clone(), parallelism, move |client, (mev_order, funding)| {
+            let taker_traits = taker_traits.clone();
+            let taker_setup = taker_setup.clone();
+            async move {
+                let mut order_config = mev_order.to_standard_order(&taker_traits)?;
+                order_config.funding = Some(funding);
+                order_config.taker_setup = taker_setup;
+                find_max_fillable(&order_config, &mev_order.order.extension, &client).await
+            }
+        }).await;
+        for (i, search) in searches.iter().enumerate() {
+            match search {
+                Ok(Some(max_fill)) => info!("MEV Order #{} max fill: {} making for {} taking", i + 1, max_fill.making_amount, max_fill.taking_amount),
+                Ok(None) => info!("MEV Order #{} has no fillable amount", i + 1),
+                Err(e) => error!("MEV Order #{} max fill search failed: {}", i + 1, e),
+            }
+        }
+    }
+
+    if batch {
+        // One atomic batch per fork block and pair
+        let mut groups: Vec<(ForkBlock, Vec<(MevOneInchOrder, FundingStrategy)>)> = Vec::new();
+        for (block, job) in &jobs {
+            let pair = (&job.0.order.maker_asset, &job.0.order.taker_asset);
+            match groups.iter_mut().find(|(b, g)| b == block && (&g[0].0.order.maker_asset, &g[0].0.order.taker_asset) == pair) {
+                Some((_, group)) => group.push(job.clone()),
+                None => groups.push((*block, vec![job.clone()])),
+            }
+        }
+
+        let taker_traits = taker_traits.clone();
+        let taker_setup = taker_setup.clone();
+        let batches = pool.run_batch(groups, parallelism, move |client, group| {
+            let taker_traits = taker_traits.clone();
+            let taker_setup = taker_setup.clone();
+            async move {
+                let mut orders = Vec::with_capacity(group.len());
+                for (mev_order, funding) in group {
+                    let mut order_config = mev_order.to_standard_order(&taker_traits)?;
+                    order_config.funding = Some(funding);
+                    order_config.taker_setup = taker_setup.clone();
+                    orders.push((order_config, mev_order.order.extension.clone()));
                 }
+                fill_orders_batch(&orders, &client, mode, true).await
             }
-            Err(e) => error!("Failed to convert MEV order to standard order: {}", e),
+        }).await;
+        for (i, batch) in batches.iter().enumerate() {
+            match batch {
+                Ok(fill) if fill.is_success() => info!("Batch #{} filled {} order(s)", i + 1, fill.fills.len()),
+                Ok(fill) => match &fill.error {
+                    Some(error) => error!("Batch #{} reverted: {}", i + 1, error),
+                    None => error!("Batch #{}: {} of {} order(s) failed", i + 1, fill.fills.iter().filter(|f| f.error.is_some()).count(), fill.fills.len()),
+                },
+                Err(e) => error!("Batch #{} failed: {}", i + 1, e),
             }
-            info!("");
+        }
+    }
+
+    let results = pool.run_batch(jobs, parallelism, move |client, (mev_order, funding)| {
+        let taker_traits = taker_traits.clone();
+        let taker_setup = taker_setup.clone();
+        async move {
+            let mut order_config = mev_order.to_standard_order(&taker_traits)
+                .map_err(|e| eyre!("Failed to convert MEV order to standard order: {}", e))?;
+            order_config.funding = Some(funding);
+            if flash_fill {
+                order_config.taker_interaction = Some(TakerInteraction::PancakeV2FlashFill);
+            }
+            order_config.taker_allowance = taker_allowance;
+            order_config.pay_native = pay_native;
+            order_config.unwrap_native = unwrap_native;
+            order_config.taker_setup = taker_setup;
+            fill_order_args(&order_config, &mev_order.order.extension, &client, mode).await
+        }
+    }).await;
+
+    for (mev_order_index, result) in results.iter().enumerate() {
+        match result {
+            Ok(outcome) if outcome.is_success() => info!("MEV Order #{} completed successfully", mev_order_index + 1),
+            Ok(outcome) if outcome.is_maker_insolvent() => warn!("MEV Order #{} skipped, {}", mev_order_index + 1, outcome.revert_reason.as_deref().unwrap_or_default()),
+            Ok(outcome) => error!("MEV Order #{} reverted: {}", mev_order_index + 1, outcome.revert_reason.as_deref().unwrap_or_default()),
+            Err(e) => error!("MEV Order #{} failed: {}", mev_order_index + 1, e),
+        }
+    }
+
+    let labels: Vec<String> = (1..=results.len()).map(|i| format!("MEV Order #{}", i)).collect();
+    log_summary_table(&labels, &results);
+
+    if let Some(path) = report_path {
+        let rows = labels.iter()
+            .zip(&blocks)
+            .zip(mev_config.one_inch_orders.iter().zip(&results))
+            .map(|((label, block), (order, result))| ReportRow::for_mev_order(label, *block, order, result))
+            .collect();
+        SimulationReport::new(mode, rows).write(std::path::Path::new(&path))?;
     }
 
     info!("MEV Demo completed successfully");
     Ok(())
 }
+
+fn flag_number(args: &[String], flag: &str) -> Result<Option<usize>> {
+    flag_value(args, flag)
+        .map(|v| v.parse::<usize>().map_err(|e| eyre!("Invalid value for {}: {}", flag, e)))
+        .transpose()
+}
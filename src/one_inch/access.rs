@@ -0,0 +1,166 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::{types::{Address, U256}, utils::parse_units};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::anvil_setup::{SignerClient, get_token_balance, impersonate_account};
+use crate::bindings::aggregation_router_v6::Order;
+use crate::bindings::erc20::IERC20;
+use crate::config::simple_config::{TakerAccount, TakerSetup};
+use crate::funding::fund_account;
+use super::extension::OrderExtension;
+use super::maker_traits::MakerTraits;
+
+/// Something in the order that limits who may fill it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessRequirement {
+    /// MakerTraits allowedSender: the taker's address must end in these 10 bytes
+    AllowedSender(u128),
+    /// Interaction on an extension contract that can reject takers, like a Fusion settlement's
+    /// resolver whitelist or access token check; `fields` names the extension fields calling it
+    ExtensionGate { target: Address, fields: Vec<&'static str> },
+}
+
+impl fmt::Display for AccessRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessRequirement::AllowedSender(suffix) => write!(f, "taker address must end in 0x{:020x}", suffix),
+            AccessRequirement::ExtensionGate { target, fields } => write!(f, "{} on {:?} may gate takers", fields.join(" and "), target),
+        }
+    }
+}
+
+/// Access requirements readable from the order and its extension
+pub fn access_requirements(order: &Order, extension: &OrderExtension) -> Vec<AccessRequirement> {
+    let mut requirements = Vec::new();
+    let allowed_sender = MakerTraits(order.maker_traits).allowed_sender();
+    if allowed_sender != 0 {
+        requirements.push(AccessRequirement::AllowedSender(allowed_sender));
+    }
+
+    let mut gates: Vec<(Address, Vec<&'static str>)> = Vec::new();
+    for (name, data) in [("PreInteraction", &extension.pre_interaction_data), ("PostInteraction", &extension.post_interaction_data)] {
+        if data.len() < 20 {
+            continue;
+        }
+        let target = Address::from_slice(&data[..20]);
+        match gates.iter_mut().find(|(t, _)| *t == target) {
+            Some((_, fields)) => fields.push(name),
+            None => gates.push((target, vec![name])),
+        }
+    }
+    requirements.extend(gates.into_iter().map(|(target, fields)| AccessRequirement::ExtensionGate { target, fields }));
+    requirements
+}
+
+/// Account the fill is sent from: the signer, or the setup's `taker` impersonated on the fork
+pub async fn resolve_taker(client: &Arc<SignerClient>, setup: Option<&TakerSetup>, order: &Order) -> Result<Address> {
+    let taker = match setup.and_then(|s| s.taker.as_ref()) {
+        None => return Ok(client.address()),
+        Some(TakerAccount::Address { address }) => Address::from_str(address)?,
+        Some(TakerAccount::AllowedSender) => match MakerTraits(order.maker_traits).allowed_sender() {
+            0 => return Ok(client.address()),
+            suffix => allowed_sender_address(suffix),
+        },
+    };
+    if taker != client.address() {
+        impersonate_account(client, taker).await?;
+        info!("🎭 Filling as impersonated taker {:?}", taker);
+    }
+    Ok(taker)
+}
+
+/// `hold_tokens` amount in base units: whole tokens when it has a decimal point, else base
+/// units in decimal or 0x hex
+fn parse_token_amount(amount: &str, decimals: u8) -> Result<U256> {
+    if let Some((_, fraction)) = amount.split_once('.') {
+        if fraction.len() > decimals as usize {
+            return Err(eyre::eyre!("{} has more than the token's {} decimals", amount, decimals));
+        }
+        return Ok(parse_units(amount, decimals as u32)?.into());
+    }
+    Ok(match amount.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16)?,
+        None => U256::from_dec_str(amount)?,
+    })
+}
+
+/// Address ending in the 10 allowedSender bytes, zero above them
+fn allowed_sender_address(suffix: u128) -> Address {
+    let mut bytes = [0u8; 20];
+    bytes[4..].copy_from_slice(&suffix.to_be_bytes());
+    Address::from(bytes)
+}
+
+/// Log the order's access requirements and give `taker` what `setup` asks for; nothing
+/// is funded without a setup
+pub async fn apply_taker_setup(
+    client: &Arc<SignerClient>,
+    taker: Address,
+    setup: Option<&TakerSetup>,
+    requirements: &[AccessRequirement],
+) -> Result<()> {
+    for requirement in requirements {
+        info!("🔐 Access requirement: {}", requirement);
+    }
+
+    let Some(setup) = setup.filter(|s| !s.hold_tokens.is_empty()) else {
+        if requirements.iter().any(|r| matches!(r, AccessRequirement::ExtensionGate { .. })) {
+            warn!("⚠️  No taker setup; if the extension rejects the taker, add its access token to taker_setup.hold_tokens");
+        }
+        return Ok(());
+    };
+
+    let funding = setup.funding.clone().unwrap_or_default();
+    for (token, amount) in &setup.hold_tokens {
+        let token = Address::from_str(token)?;
+        let decimals = match amount.contains('.') {
+            true => IERC20::new(token, client.clone()).decimals().call().await?,
+            false => 0,
+        };
+        let amount = parse_token_amount(amount, decimals)?;
+        if get_token_balance(client, token, taker).await? >= amount {
+            continue;
+        }
+        fund_account(client, token, taker, amount, &funding).await?;
+        info!("🎟️  Taker {:?} holds {} of {:?} per taker setup", taker, amount, token);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowed_sender_address_satisfies_the_suffix() {
+        let resolver = Address::from_str("0x1111111111111111111122334455667788990011").unwrap();
+        let suffix = u128::from_be_bytes({
+            let mut bytes = [0u8; 16];
+            bytes[6..].copy_from_slice(&resolver.as_bytes()[10..]);
+            bytes
+        });
+        let taker = allowed_sender_address(suffix);
+        assert_eq!(taker, Address::from_str("0x0000000000000000000022334455667788990011").unwrap());
+        assert_eq!(taker.as_bytes()[10..], resolver.as_bytes()[10..]);
+    }
+
+    #[test]
+    fn token_amounts() {
+        assert_eq!(parse_token_amount("1.0", 18).unwrap(), U256::exp10(18));
+        assert_eq!(parse_token_amount("1.5", 6).unwrap(), U256::from(1_500_000));
+        assert_eq!(parse_token_amount("1000", 18).unwrap(), U256::from(1000));
+        assert_eq!(parse_token_amount("0x10", 18).unwrap(), U256::from(16));
+        assert!(parse_token_amount("0.0000001", 6).is_err());
+    }
+
+    #[test]
+    fn taker_account_config() {
+        let setup: TakerSetup = serde_json::from_str(r#"{"taker": {"kind": "AllowedSender"}}"#).unwrap();
+        assert_eq!(setup.taker, Some(TakerAccount::AllowedSender));
+        let setup: TakerSetup = serde_json::from_str(r#"{"taker": {"kind": "Address", "address": "0x1111111111111111111122334455667788990011"}}"#).unwrap();
+        assert!(matches!(setup.taker, Some(TakerAccount::Address { .. })));
+    }
+}
@@ -0,0 +1,411 @@
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+use ethers::{
+    abi::{decode, ParamType},
+    types::{Address, BlockNumber, Bytes, U256},
+};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::anvil_setup::SignerClient;
+use crate::bindings::aggregation_router_v6::Order;
+use crate::bindings::amount_getter::{self, IAmountGetter};
+use super::block_timestamp;
+use super::extension::OrderExtension;
+
+const LOW_128_BITS: u128 = u128::MAX;
+
+/// Calculators known up front, comma-separated in these env vars; others are recognised on
+/// the fork by `confirm_calculators`
+const DUTCH_AUCTION_CALCULATORS_ENV: &str = "DUTCH_AUCTION_CALCULATORS";
+const RANGE_AMOUNT_CALCULATORS_ENV: &str = "RANGE_AMOUNT_CALCULATORS";
+
+/// Getter addresses whose math we reproduce off-chain
+#[derive(Debug, Clone, Default)]
+pub struct KnownCalculators {
+    pub dutch_auction: Vec<Address>,
+    pub range: Vec<Address>,
+}
+
+impl KnownCalculators {
+    /// Calculators listed in DUTCH_AUCTION_CALCULATORS and RANGE_AMOUNT_CALCULATORS
+    pub fn from_env() -> Self {
+        let addresses = |var: &str| -> Vec<Address> {
+            let list = std::env::var(var).unwrap_or_default();
+            list.split(',').map(str::trim).filter(|a| !a.is_empty()).filter_map(|a| {
+                Address::from_str(a).map_err(|e| warn!("⚠️  Ignoring {} entry {}: {}", var, a, e)).ok()
+            }).collect()
+        };
+        Self { dutch_auction: addresses(DUTCH_AUCTION_CALCULATORS_ENV), range: addresses(RANGE_AMOUNT_CALCULATORS_ENV) }
+    }
+}
+
+/// Process-wide calculator list: the env lists plus getters confirmed on the fork
+fn known_calculators() -> &'static RwLock<KnownCalculators> {
+    static CALCULATORS: OnceLock<RwLock<KnownCalculators>> = OnceLock::new();
+    CALCULATORS.get_or_init(|| RwLock::new(KnownCalculators::from_env()))
+}
+
+/// Recognise the order's unlisted getters on the fork: a getter whose extraData decodes as a
+/// Dutch auction or range and whose `getTakingAmount` matches the model at the latest block
+/// is remembered as that calculator
+pub async fn confirm_calculators(client: &Arc<SignerClient>, order: &Order, extension: &OrderExtension) -> Result<()> {
+    let timestamp = block_timestamp(client, BlockNumber::Latest).await?;
+    for field in [&extension.making_amount_data, &extension.taking_amount_data] {
+        let AmountGetter::Unknown { getter, data } = AmountGetter::from_field(field) else {
+            continue;
+        };
+        let candidates = [
+            KnownCalculators { dutch_auction: vec![getter], range: Vec::new() },
+            KnownCalculators { dutch_auction: Vec::new(), range: vec![getter] },
+        ];
+        for candidate in candidates {
+            let model = AmountGetter::from_field_with(field, &candidate);
+            let making_amount = (order.making_amount / 2).max(U256::one());
+            let Some(expected) = model.taking_amount(order, making_amount, order.making_amount, timestamp) else {
+                continue;
+            };
+            let on_fork = IAmountGetter::new(getter, client.clone())
+                .get_taking_amount(getter_order(order), extension.encode(), [0u8; 32], Address::zero(), making_amount, order.making_amount, data.clone())
+                .block(BlockNumber::Latest)
+                .call()
+                .await;
+            if on_fork.ok() != Some(expected) {
+                continue;
+            }
+            info!("🧮 {:?} prices like a {}, modelling it off-chain", getter, match model {
+                AmountGetter::DutchAuction { .. } => "DutchAuctionCalculator",
+                _ => "RangeAmountCalculator",
+            });
+            let mut known = known_calculators().write().unwrap_or_else(|e| e.into_inner());
+            known.dutch_auction.extend(candidate.dutch_auction);
+            known.range.extend(candidate.range);
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn getter_order(order: &Order) -> amount_getter::Order {
+    amount_getter::Order {
+        salt: order.salt,
+        maker: order.maker,
+        receiver: order.receiver,
+        maker_asset: order.maker_asset,
+        taker_asset: order.taker_asset,
+        making_amount: order.making_amount,
+        taking_amount: order.taking_amount,
+        maker_traits: order.maker_traits,
+    }
+}
+
+/// Off-chain model of the getter an order uses for making or taking amounts
+///
+/// Getters are recognised by address (see `KnownCalculators`), then their `extraData` is decoded:
+/// - DutchAuctionCalculator: `abi.encode(startTime << 128 | endTime, takingAmountStart, takingAmountEnd)`
+/// - RangeAmountCalculator: `abi.encode(priceStart, priceEnd)`, prices scaled by 1e18
+///
+/// Any other getter, or a known one with data it wouldn't accept, is `Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmountGetter {
+    /// No getter: amounts scale linearly with the order's making/taking ratio
+    Proportional,
+    DutchAuction { start_time: u64, end_time: u64, taking_amount_start: U256, taking_amount_end: U256 },
+    Range { price_start: U256, price_end: U256 },
+    /// A getter we can't reproduce off-chain
+    Unknown { getter: Address, data: Bytes },
+}
+
+impl AmountGetter {
+    /// Model for a `MakingAmountData`/`TakingAmountData` extension field (`[getter][extraData]`)
+    pub fn from_field(field: &[u8]) -> Self {
+        Self::from_field_with(field, &known_calculators().read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// `from_field` against an explicit calculator list
+    pub fn from_field_with(field: &[u8], calculators: &KnownCalculators) -> Self {
+        if field.is_empty() {
+            return AmountGetter::Proportional;
+        }
+        if field.len() < 20 {
+            return AmountGetter::Unknown { getter: Address::zero(), data: Bytes::from(field.to_vec()) };
+        }
+
+        let getter = Address::from_slice(&field[..20]);
+        let data = &field[20..];
+        if calculators.dutch_auction.contains(&getter) {
+            let words = decode(&[ParamType::Uint(256), ParamType::Uint(256), ParamType::Uint(256)], data).ok()
+                .filter(|_| data.len() == 96)
+                .map(|tokens| tokens.into_iter().filter_map(|t| t.into_uint()).collect::<Vec<_>>());
+            if let Some([start_end, taking_amount_start, taking_amount_end]) = words.as_deref() {
+                let start_time = *start_end >> 128;
+                let end_time = *start_end & U256::from(LOW_128_BITS);
+                if start_time < end_time && end_time <= U256::from(u64::MAX) {
+                    return AmountGetter::DutchAuction {
+                        start_time: start_time.as_u64(),
+                        end_time: end_time.as_u64(),
+                        taking_amount_start: *taking_amount_start,
+                        taking_amount_end: *taking_amount_end,
+                    };
+                }
+            }
+        } else if calculators.range.contains(&getter) {
+            let words = decode(&[ParamType::Uint(256), ParamType::Uint(256)], data).ok()
+                .filter(|_| data.len() == 64)
+                .map(|tokens| tokens.into_iter().filter_map(|t| t.into_uint()).collect::<Vec<_>>());
+            if let Some([price_start, price_end]) = words.as_deref() {
+                // RangeAmountCalculator reverts with IncorrectRange otherwise
+                if price_start <= price_end {
+                    return AmountGetter::Range { price_start: *price_start, price_end: *price_end };
+                }
+            }
+        }
+        AmountGetter::Unknown { getter, data: Bytes::from(data.to_vec()) }
+    }
+
+    /// Making amount the router would compute for `taking_amount`, None when we can't model it
+    pub fn making_amount(&self, order: &Order, taking_amount: U256, remaining_making_amount: U256, timestamp: u64) -> Option<U256> {
+        match self {
+            AmountGetter::Proportional => order.making_amount.checked_mul(taking_amount)?.checked_div(order.taking_amount),
+            AmountGetter::DutchAuction { .. } => {
+                let auction_taking_amount = self.auction_taking_amount(timestamp)?;
+                order.making_amount.checked_mul(taking_amount)?.checked_div(auction_taking_amount)
+            }
+            AmountGetter::Range { price_start, price_end } => {
+                range_making_amount(*price_start, *price_end, order.making_amount, taking_amount, remaining_making_amount)
+            }
+            AmountGetter::Unknown { .. } => None,
+        }
+    }
+
+    /// Taking amount the router would compute for `making_amount`, None when we can't model it
+    pub fn taking_amount(&self, order: &Order, making_amount: U256, remaining_making_amount: U256, timestamp: u64) -> Option<U256> {
+        match self {
+            AmountGetter::Proportional => ceil_div(order.taking_amount.checked_mul(making_amount)?, order.making_amount),
+            AmountGetter::DutchAuction { .. } => {
+                let auction_taking_amount = self.auction_taking_amount(timestamp)?;
+                ceil_div(making_amount.checked_mul(auction_taking_amount)?, order.making_amount)
+            }
+            AmountGetter::Range { price_start, price_end } => {
+                range_taking_amount(*price_start, *price_end, order.making_amount, making_amount, remaining_making_amount)
+            }
+            AmountGetter::Unknown { .. } => None,
+        }
+    }
+
+    /// Taking amount for the whole order at `timestamp`, clamped to the auction window
+    fn auction_taking_amount(&self, timestamp: u64) -> Option<U256> {
+        let AmountGetter::DutchAuction { start_time, end_time, taking_amount_start, taking_amount_end } = self else {
+            return None;
+        };
+        let now = timestamp.clamp(*start_time, *end_time);
+        let elapsed = U256::from(now - start_time);
+        let left = U256::from(end_time - now);
+        taking_amount_start.checked_mul(left)?
+            .checked_add(taking_amount_end.checked_mul(elapsed)?)?
+            .checked_div(U256::from(end_time - start_time))
+    }
+}
+
+/// Making and taking amounts a fill should produce, computed off-chain like `OrderMixin._fill`
+#[derive(Debug, Clone)]
+pub struct ExpectedFill {
+    pub making_getter: AmountGetter,
+    pub taking_getter: AmountGetter,
+    pub making_amount: U256,
+    pub taking_amount: U256,
+    pub timestamp: u64,
+}
+
+impl ExpectedFill {
+    /// None when a getter the fill needs is unknown or the math overflows
+    pub fn compute(
+        order: &Order,
+        extension: &OrderExtension,
+        amount: U256,
+        is_making_amount: bool,
+        remaining_making_amount: U256,
+        timestamp: u64,
+    ) -> Option<Self> {
+        let making_getter = AmountGetter::from_field(&extension.making_amount_data);
+        let taking_getter = AmountGetter::from_field(&extension.taking_amount_data);
+
+        let (making_amount, taking_amount) = if is_making_amount {
+            let making = amount.min(remaining_making_amount);
+            (making, taking_getter.taking_amount(order, making, remaining_making_amount, timestamp)?)
+        } else {
+            let making = making_getter.making_amount(order, amount, remaining_making_amount, timestamp)?;
+            if making > remaining_making_amount {
+                // The router caps at the remaining amount and re-prices the taking side
+                let making = remaining_making_amount;
+                (making, taking_getter.taking_amount(order, making, remaining_making_amount, timestamp)?)
+            } else {
+                (making, amount)
+            }
+        };
+
+        Some(Self { making_getter, taking_getter, making_amount, taking_amount, timestamp })
+    }
+
+    pub fn log_summary(&self) {
+        info!("🧮 Off-chain amount getters at timestamp {}:", self.timestamp);
+        info!("  Making getter: {:?}", self.making_getter);
+        info!("  Taking getter: {:?}", self.taking_getter);
+        info!("  Expected Making Amount: {} wei", self.making_amount);
+        info!("  Expected Taking Amount: {} wei", self.taking_amount);
+    }
+}
+
+/// RangeAmountCalculator.getRangeTakerAmount: the price moves linearly from `price_start` to
+/// `price_end` as the order fills, and the taker pays the average price over its slice
+fn range_taking_amount(price_start: U256, price_end: U256, order_making_amount: U256, making_amount: U256, remaining_making_amount: U256) -> Option<U256> {
+    let already_filled = order_making_amount.checked_sub(remaining_making_amount)?;
+    let slope = (price_end - price_start)
+        .checked_mul(already_filled.checked_mul(2.into())?.checked_add(making_amount)?)?
+        .checked_div(order_making_amount)?;
+    slope.checked_add(price_start.checked_mul(2.into())?)?
+        .checked_mul(making_amount)?
+        .checked_div(U256::exp10(18) * 2)
+}
+
+/// RangeAmountCalculator.getRangeMakerAmount: inverse of `range_taking_amount`, solving
+/// `m^2 + 2bm = 2e18 * T * M / (priceEnd - priceStart)` with `b = priceStart * M / diff + filled`
+fn range_making_amount(price_start: U256, price_end: U256, order_making_amount: U256, taking_amount: U256, remaining_making_amount: U256) -> Option<U256> {
+    let already_filled = order_making_amount.checked_sub(remaining_making_amount)?;
+    let price_diff = price_end - price_start;
+    if price_diff.is_zero() {
+        return taking_amount.checked_mul(U256::exp10(18))?.checked_div(price_start);
+    }
+
+    let b = price_start.checked_mul(order_making_amount)?.checked_div(price_diff)?.checked_add(already_filled)?;
+    let c = taking_amount.checked_mul(U256::exp10(18) * 2)?.checked_mul(order_making_amount)?.checked_div(price_diff)?;
+    b.checked_mul(b)?.checked_add(c)?.integer_sqrt().checked_sub(b)
+}
+
+fn ceil_div(a: U256, b: U256) -> Option<U256> {
+    if b.is_zero() {
+        return None;
+    }
+    Some(a.checked_add(b - 1)? / b)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{encode, Token};
+    use super::*;
+
+    fn calculators() -> KnownCalculators {
+        KnownCalculators { dutch_auction: vec![Address::repeat_byte(0xda)], range: vec![Address::repeat_byte(0x7a)] }
+    }
+
+    fn field(getter: Address, words: &[U256]) -> Vec<u8> {
+        let mut field = getter.as_bytes().to_vec();
+        field.extend(encode(&words.iter().map(|w| Token::Uint(*w)).collect::<Vec<_>>()));
+        field
+    }
+
+    #[test]
+    fn recognises_calculators_by_address() {
+        let auction = [(U256::from(100) << 128) | U256::from(200), U256::from(1000), U256::from(500)];
+        assert_eq!(
+            AmountGetter::from_field_with(&field(Address::repeat_byte(0xda), &auction), &calculators()),
+            AmountGetter::DutchAuction { start_time: 100, end_time: 200, taking_amount_start: U256::from(1000), taking_amount_end: U256::from(500) },
+        );
+        let range = [U256::exp10(18), U256::exp10(18) * 2];
+        assert_eq!(
+            AmountGetter::from_field_with(&field(Address::repeat_byte(0x7a), &range), &calculators()),
+            AmountGetter::Range { price_start: range[0], price_end: range[1] },
+        );
+    }
+
+    #[test]
+    fn same_layout_elsewhere_is_unknown() {
+        let range = [U256::exp10(18), U256::exp10(18) * 2];
+        let getter = AmountGetter::from_field_with(&field(Address::repeat_byte(0x11), &range), &calculators());
+        assert!(matches!(getter, AmountGetter::Unknown { .. }));
+        // Dutch auction data at the range calculator
+        let auction = [(U256::from(100) << 128) | U256::from(200), U256::from(1000), U256::from(500)];
+        let getter = AmountGetter::from_field_with(&field(Address::repeat_byte(0x7a), &auction), &calculators());
+        assert!(matches!(getter, AmountGetter::Unknown { .. }));
+    }
+
+    #[test]
+    fn captured_fusion_getter_is_unknown() {
+        let extension: OrderExtension = "0x000000980000004a0000004a0000004a0000004a000000250000000000000000c0dfdb9e7a392c3dbbe7c6fbe8fbc1789c9fe05e00000001f43201555d67e125f8769284bac0dfdb9e7a392c3dbbe7c6fbe8fbc1789c9fe05e00000001f43201555d67e125f8769284bac0dfdb9e7a392c3dbbe7c6fbe8fbc1789c9fe05e00000000000000000000000000000000000000000090cbe4bdd538d6e9b379bff5fe72c3d67a521de500000001f43201555d67e125f8769284ba".parse().unwrap();
+        let getter = AmountGetter::from_field_with(&extension.making_amount_data, &calculators());
+        assert!(matches!(getter, AmountGetter::Unknown { .. }));
+        assert_eq!(AmountGetter::from_field_with(&[], &calculators()), AmountGetter::Proportional);
+    }
+
+    fn ether(amount: u64) -> U256 {
+        U256::from(amount) * U256::exp10(18)
+    }
+
+    fn order(making_amount: U256, taking_amount: U256) -> Order {
+        Order { making_amount, taking_amount, ..Default::default() }
+    }
+
+    /// Upstream's DutchAuctionCalculator test: 100 DAI for 0.1 ETH falling to 0.05 ETH over a day
+    fn dutch_auction() -> AmountGetter {
+        AmountGetter::DutchAuction { start_time: 1_000, end_time: 1_000 + 86_400, taking_amount_start: ether(1) / 10, taking_amount_end: ether(1) / 20 }
+    }
+
+    #[test]
+    fn dutch_auction_interpolates_over_the_window() {
+        let order = order(ether(100), ether(1) / 10);
+        let getter = dutch_auction();
+        for (timestamp, taking) in [(0, ether(1) / 10), (1_000, ether(1) / 10), (44_200, ether(75) / 1000), (87_400, ether(1) / 20), (200_000, ether(1) / 20)] {
+            assert_eq!(getter.taking_amount(&order, ether(100), ether(100), timestamp), Some(taking), "at {}", timestamp);
+            assert_eq!(getter.making_amount(&order, taking, ether(100), timestamp), Some(ether(100)), "at {}", timestamp);
+        }
+        // Half the order halfway through, the taking side rounds up
+        assert_eq!(getter.taking_amount(&order, ether(50), ether(100), 44_200), Some(ether(375) / 10_000));
+        assert_eq!(getter.taking_amount(&order, U256::one(), ether(100), 44_200), Some(U256::one()));
+    }
+
+    #[test]
+    fn range_amounts() {
+        // 10 tokens priced from 3000 to 4000 as they fill
+        let (start, end) = (ether(3000), ether(4000));
+        assert_eq!(range_taking_amount(start, end, ether(10), ether(10), ether(10)), Some(ether(35_000)));
+        assert_eq!(range_taking_amount(start, end, ether(10), ether(5), ether(10)), Some(ether(16_250)));
+        assert_eq!(range_taking_amount(start, end, ether(10), ether(5), ether(5)), Some(ether(18_750)));
+        assert_eq!(range_making_amount(start, end, ether(10), ether(16_250), ether(10)), Some(ether(5)));
+        assert_eq!(range_making_amount(start, end, ether(10), ether(18_750), ether(5)), Some(ether(5)));
+        assert_eq!(range_making_amount(start, end, ether(10), ether(35_000), ether(10)), Some(ether(10)));
+        // Flat range is a fixed price
+        assert_eq!(range_making_amount(start, start, ether(10), ether(6_000), ether(10)), Some(ether(2)));
+        // More remaining than the order is malformed
+        assert_eq!(range_taking_amount(start, end, ether(10), ether(5), ether(11)), None);
+    }
+
+    #[test]
+    fn compute_with_calculators() {
+        let (auction, range) = (Address::repeat_byte(0xd1), Address::repeat_byte(0x71));
+        {
+            let mut known = known_calculators().write().unwrap();
+            known.dutch_auction.push(auction);
+            known.range.push(range);
+        }
+
+        // Taking-amount fill of the Dutch auction at its midpoint
+        let auction_data = [(U256::from(1_000) << 128) | U256::from(87_400), ether(1) / 10, ether(1) / 20];
+        let mut extension = OrderExtension::default();
+        extension.making_amount_data = field(auction, &auction_data).into();
+        extension.taking_amount_data = field(auction, &auction_data).into();
+        let dutch_order = order(ether(100), ether(1) / 10);
+        let fill = ExpectedFill::compute(&dutch_order, &extension, ether(75) / 1000, false, ether(100), 44_200).unwrap();
+        assert_eq!((fill.making_amount, fill.taking_amount), (ether(100), ether(75) / 1000));
+        // Asking for more than the remaining amount re-prices the capped making amount
+        let fill = ExpectedFill::compute(&dutch_order, &extension, ether(1) / 10, false, ether(40), 44_200).unwrap();
+        assert_eq!((fill.making_amount, fill.taking_amount), (ether(40), ether(3) / 100));
+
+        // Making-amount fill of the second half of the range
+        let range_data = [ether(3000), ether(4000)];
+        let mut extension = OrderExtension::default();
+        extension.making_amount_data = field(range, &range_data).into();
+        extension.taking_amount_data = field(range, &range_data).into();
+        let fill = ExpectedFill::compute(&order(ether(10), ether(35_000)), &extension, ether(5), true, ether(5), 0).unwrap();
+        assert_eq!((fill.making_amount, fill.taking_amount), (ether(5), ether(18_750)));
+    }
+}
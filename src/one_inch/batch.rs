@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::{
+    abi::AbiDecode,
+    types::{Address, H256, U256},
+};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::anvil_setup::{SignerClient, deploy_batch_taker};
+use crate::batch_taker::{BatchCall, CallResult, decode_results, encode_calls};
+use crate::bindings::aggregation_router_v6::AggregationRouterV6;
+use crate::bindings::batch_taker::BatchTaker;
+use crate::bindings::erc20::IERC20;
+use crate::config::simple_config::{FundingStrategy, OneInchOrder};
+use crate::execution::{BalanceWatch, ExecutionMode, ExecutionReceipt, execute_call, reverted_receipt};
+use crate::funding::fund_account;
+use crate::revert::SimulationError;
+use crate::simulation::SimulationOutcome;
+use super::access::{access_requirements, apply_taker_setup};
+use super::amount_getters::confirm_calculators;
+use super::extension::OrderExtension;
+use super::signature::SignatureCheck;
+use super::taker_traits::TakerTraits;
+use super::{
+    AGGREGATION_ROUTER_V6, check_order_predicate, check_solvency, clamp_to_remaining, fill_inputs,
+    parse_contract_signature, parse_order, parse_signature, preflight, unpack_address,
+};
+
+/// Result of one order inside a batch
+#[derive(Debug, Clone)]
+pub struct OrderFill {
+    pub index: usize,
+    pub making_amount: Option<U256>,
+    pub taking_amount: Option<U256>,
+    pub order_hash: Option<H256>,
+    pub error: Option<SimulationError>,
+}
+
+/// Result of filling several orders through the batch taker
+#[derive(Debug, Clone)]
+pub struct BatchFill {
+    pub executor: Address,
+    pub atomic: bool,
+    pub fills: Vec<OrderFill>,
+    /// Mined transaction, `Send` mode only
+    pub receipt: Option<ExecutionReceipt>,
+    /// Why the whole batch reverted (atomic batches only)
+    pub error: Option<SimulationError>,
+}
+
+impl OrderFill {
+    /// An order the pre-fill checks kept out of the batch
+    fn rejected(index: usize, outcome: SimulationOutcome) -> Self {
+        Self { index, making_amount: None, taking_amount: None, order_hash: None, error: outcome.error }
+    }
+}
+
+impl BatchFill {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && self.fills.iter().all(|f| f.error.is_none())
+    }
+
+    pub fn log_summary(&self) {
+        info!("📦 Batch fill via {:?} ({}):", self.executor, if self.atomic { "atomic" } else { "best effort" });
+        if let Some(error) = &self.error {
+            info!("  Batch reverted: {}", error);
+        }
+        for fill in &self.fills {
+            match (&fill.error, fill.making_amount, fill.taking_amount) {
+                (None, Some(making), Some(taking)) => info!("  Order #{}: ✅ making {}, taking {}", fill.index + 1, making, taking),
+                (Some(error), _, _) => info!("  Order #{}: ❌ {}", fill.index + 1, error),
+                _ => info!("  Order #{}: not reached", fill.index + 1),
+            }
+        }
+    }
+}
+
+/// Fill `orders` (order config and extension hex) in one transaction from the batch taker
+///
+/// The executor is funded with the summed taker amounts and approves the router itself, so
+/// the router sees it as the taker and the maker assets end up in it. Each order first goes
+/// through the single-fill pre-flight, predicate and solvency checks with the executor as
+/// taker, and the executor gets the order's taker setup; rejected orders are left out of the
+/// batch and reported in `fills`. With `atomic` any failing fill reverts the batch; the
+/// per-order outcomes are then recovered with a best-effort `eth_call` of the same batch.
+pub async fn fill_orders_batch(
+    orders: &[(OneInchOrder, String)],
+    client: &Arc<SignerClient>,
+    mode: ExecutionMode,
+    atomic: bool,
+) -> Result<BatchFill> {
+    info!("🔄 Batch filling {} 1inch order(s) ({}, {})...", orders.len(), mode, if atomic { "atomic" } else { "best effort" });
+    let executor = deploy_batch_taker(client).await?;
+    let router = AggregationRouterV6::new(Address::from_str(AGGREGATION_ROUTER_V6)?, client.clone());
+
+    // Taker asset -> summed amount and the funding of the first order paying in it
+    let mut taker_totals: BTreeMap<Address, (U256, FundingStrategy)> = BTreeMap::new();
+    let mut maker_assets = Vec::new();
+    let mut rejected = Vec::new();
+    let mut sent = Vec::with_capacity(orders.len());
+    let mut fill_calls = Vec::with_capacity(orders.len());
+    for (index, (order_config, extension_data)) in orders.iter().enumerate() {
+        let order = parse_order(order_config)?;
+        let (r, vs) = parse_signature(order_config)?;
+        let amount = U256::from_dec_str(&order_config.amount)?;
+        let extension = OrderExtension::from_str(extension_data)?;
+        confirm_calculators(client, &order, &extension).await?;
+        let (taker_traits, args) = TakerTraits::new().extension(extension.encode()).encode()?;
+
+        let mut outcome = SimulationOutcome::new("1inch", mode, fill_inputs(&order, amount));
+        let Some((remaining, signature)) = preflight(client, order_config, &order, amount, taker_traits, executor, &mut outcome).await? else {
+            rejected.push(OrderFill::rejected(index, outcome));
+            continue;
+        };
+        let amount = clamp_to_remaining(amount, remaining, taker_traits);
+        if !check_order_predicate(client, &extension, &mut outcome).await?
+            || !check_solvency(client, &order, &extension, amount, taker_traits, remaining, &mut outcome).await? {
+            rejected.push(OrderFill::rejected(index, outcome));
+            continue;
+        }
+
+        // The executor is the taker, so it gets what the setup gives; it can't be another account
+        if order_config.taker_setup.as_ref().is_some_and(|s| s.taker.is_some()) {
+            warn!("⚠️  Order #{} sets taker_setup.taker, the batch fills from the executor {:?} instead", index + 1, executor);
+        }
+        apply_taker_setup(client, executor, order_config.taker_setup.as_ref(), &access_requirements(&order, &extension)).await?;
+
+        let taker_asset = unpack_address(order.taker_asset);
+        let funding = order_config.funding.clone().unwrap_or_default();
+        taker_totals.entry(taker_asset).or_insert((U256::zero(), funding)).0 += amount;
+        maker_assets.push(unpack_address(order.maker_asset));
+        sent.push(index);
+
+        let data = match signature {
+            SignatureCheck::Erc1271 => router.fill_contract_order_args(order, parse_contract_signature(order_config)?, amount, taker_traits, args).calldata(),
+            _ => router.fill_order_args(order, r, vs, amount, taker_traits, args).calldata(),
+        }.ok_or_else(|| eyre::eyre!("Failed to encode fillOrderArgs"))?;
+        fill_calls.push(BatchCall { target: router.address(), value: U256::zero(), allow_failure: !atomic, data });
+    }
+
+    if sent.is_empty() {
+        warn!("⛔ Every order failed its checks, nothing to batch");
+        let result = BatchFill { executor, atomic, fills: rejected, receipt: None, error: None };
+        result.log_summary();
+        return Ok(result);
+    }
+
+    // Fund the executor and have it approve the router for each taker asset
+    let mut calls = Vec::new();
+    for (taker_asset, (total, funding)) in &taker_totals {
+        fund_account(client, *taker_asset, executor, *total, funding).await?;
+        let data = IERC20::new(*taker_asset, client.clone()).approve(router.address(), *total).calldata()
+            .ok_or_else(|| eyre::eyre!("Failed to encode approve"))?;
+        calls.push(BatchCall { target: *taker_asset, value: U256::zero(), allow_failure: false, data });
+    }
+    let approvals = calls.len();
+    calls.extend(fill_calls);
+
+    let mut watches: Vec<BalanceWatch> = taker_totals.keys()
+        .map(|asset| BalanceWatch::new("executor taker asset", executor, *asset))
+        .collect();
+    maker_assets.sort();
+    maker_assets.dedup();
+    watches.extend(maker_assets.iter().map(|asset| BalanceWatch::new("executor maker asset", executor, *asset)));
+
+    let batch_taker = BatchTaker::new(executor, client.clone());
+    let call = batch_taker.execute(encode_calls(&calls));
+    let result = match execute_call(client, call, mode, &watches).await {
+        Ok((results, receipt)) => {
+            let results = decode_results(&results)?;
+            BatchFill { executor, atomic, fills: order_fills(&sent, &results[approvals..], rejected), receipt, error: None }
+        }
+        Err(e) => {
+            let error = SimulationError::from_report(&e);
+            warn!("⛔ Batch reverted: {}", error);
+
+            // Replay without atomicity to see which orders would have failed
+            for call in calls.iter_mut().skip(approvals) {
+                call.allow_failure = true;
+            }
+            let fills = match batch_taker.execute(encode_calls(&calls)).call().await {
+                Ok(results) => order_fills(&sent, &decode_results(&results)?[approvals..], rejected),
+                Err(e) => {
+                    warn!("Best-effort replay failed too: {}", e);
+                    rejected
+                }
+            };
+            BatchFill { executor, atomic, fills, receipt: reverted_receipt(&e), error: Some(error) }
+        }
+    };
+
+    result.log_summary();
+    Ok(result)
+}
+
+/// Fills of the batched orders (`sent` holds their indices) merged with the rejected ones, in order
+fn order_fills(sent: &[usize], results: &[CallResult], rejected: Vec<OrderFill>) -> Vec<OrderFill> {
+    let mut fills: Vec<OrderFill> = sent.iter().zip(results).map(|(&index, result)| {
+        let mut fill = OrderFill { index, making_amount: None, taking_amount: None, order_hash: None, error: None };
+        if !result.success {
+            fill.error = Some(SimulationError::decode(&result.return_data));
+            return fill;
+        }
+        match <(U256, U256, [u8; 32])>::decode(&result.return_data) {
+            Ok((making, taking, hash)) => {
+                fill.making_amount = Some(making);
+                fill.taking_amount = Some(taking);
+                fill.order_hash = Some(H256::from(hash));
+            }
+            Err(e) => fill.error = Some(SimulationError::Other(format!("undecodable fill result: {}", e))),
+        }
+        fill
+    }).collect();
+    fills.extend(rejected);
+    fills.sort_by_key(|fill| fill.index);
+    fills
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::AbiEncode;
+    use super::*;
+
+    #[test]
+    fn order_fills_merge_rejected_orders_in_order() {
+        let filled = CallResult { success: true, return_data: (U256::from(10), U256::from(20), [7u8; 32]).encode().into() };
+        let reverted = CallResult { success: false, return_data: Default::default() };
+        let rejected = vec![OrderFill { index: 1, making_amount: None, taking_amount: None, order_hash: None, error: Some(SimulationError::PrivateOrder) }];
+
+        let fills = order_fills(&[0, 2], &[filled, reverted], rejected);
+        assert_eq!(fills.iter().map(|f| f.index).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!((fills[0].making_amount, fills[0].taking_amount), (Some(U256::from(10)), Some(U256::from(20))));
+        assert_eq!(fills[0].order_hash, Some(H256::repeat_byte(7)));
+        assert!(matches!(fills[1].error, Some(SimulationError::PrivateOrder)));
+        assert!(fills[2].error.is_some() && fills[2].making_amount.is_none());
+    }
+}
@@ -0,0 +1,246 @@
+use std::str::FromStr;
+use ethers::{
+    types::{Address, Bytes, U256},
+    utils::keccak256,
+};
+use eyre::Result;
+use tracing::info;
+
+/// Number of fields located through the offsets header; `custom_data` is whatever follows
+const OFFSET_FIELDS: usize = 8;
+const HEADER_LENGTH: usize = 32;
+
+/// 1inch V6 order extension, split by the ExtensionLib offsets header
+///
+/// Bytes are `[uint32 end offsets x 8][fields...][custom data]`, where the offset of field `i`
+/// is stored in bits `32*i..32*i+32` of the header and fields are concatenated in this order.
+/// A decoded extension encodes back to exactly the bytes it came from (the order salt commits
+/// to them) until one of its fields is changed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OrderExtension {
+    pub maker_asset_suffix: Bytes,
+    pub taker_asset_suffix: Bytes,
+    /// `[getter address][data]` used instead of proportional making amounts
+    pub making_amount_data: Bytes,
+    /// `[getter address][data]` used instead of proportional taking amounts
+    pub taking_amount_data: Bytes,
+    pub predicate: Bytes,
+    /// `[token address][permit calldata]`
+    pub maker_permit: Bytes,
+    /// `[target address][data]`
+    pub pre_interaction_data: Bytes,
+    /// `[target address][data]`
+    pub post_interaction_data: Bytes,
+    pub custom_data: Bytes,
+    /// Bytes this extension was decoded from
+    raw: Bytes,
+}
+
+impl OrderExtension {
+    /// Split raw extension bytes; an empty input is an empty extension
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.is_empty() {
+            return Ok(Self::default());
+        }
+        if data.len() < HEADER_LENGTH {
+            return Err(eyre::eyre!("Extension is {} bytes, shorter than its offsets header", data.len()));
+        }
+
+        let offsets = U256::from_big_endian(&data[..HEADER_LENGTH]);
+        let body = &data[HEADER_LENGTH..];
+
+        let mut fields: Vec<Bytes> = Vec::with_capacity(OFFSET_FIELDS);
+        let mut start = 0usize;
+        for i in 0..OFFSET_FIELDS {
+            let end = ((offsets >> (32 * i)) & U256::from(u32::MAX)).as_usize();
+            if end < start || end > body.len() {
+                return Err(eyre::eyre!(
+                    "Extension field {} spans {}..{} but the body is {} bytes",
+                    i, start, end, body.len()
+                ));
+            }
+            fields.push(Bytes::from(body[start..end].to_vec()));
+            start = end;
+        }
+
+        let mut fields = fields.into_iter();
+        let mut next = || fields.next().unwrap_or_default();
+        Ok(OrderExtension {
+            maker_asset_suffix: next(),
+            taker_asset_suffix: next(),
+            making_amount_data: next(),
+            taking_amount_data: next(),
+            predicate: next(),
+            maker_permit: next(),
+            pre_interaction_data: next(),
+            post_interaction_data: next(),
+            custom_data: Bytes::from(body[start..].to_vec()),
+            raw: Bytes::from(data.to_vec()),
+        })
+    }
+
+    /// Extension bytes: the decoded input as-is while the fields are unchanged, otherwise
+    /// rebuilt from the fields (empty when every field is empty)
+    pub fn encode(&self) -> Bytes {
+        if !self.raw.is_empty() && Self::decode(&self.raw).is_ok_and(|decoded| decoded == *self) {
+            return self.raw.clone();
+        }
+        if self.offset_fields().iter().all(|f| f.is_empty()) && self.custom_data.is_empty() {
+            return Bytes::new();
+        }
+
+        let mut offsets = U256::zero();
+        let mut body = Vec::new();
+        for (i, field) in self.offset_fields().iter().enumerate() {
+            body.extend_from_slice(field);
+            offsets |= U256::from(body.len() as u32) << (32 * i);
+        }
+        body.extend_from_slice(&self.custom_data);
+
+        let mut data = vec![0u8; HEADER_LENGTH];
+        offsets.to_big_endian(&mut data);
+        data.extend_from_slice(&body);
+        data.into()
+    }
+
+    /// No extension bytes at all; an all-zero offsets header still counts as an extension
+    pub fn is_empty(&self) -> bool {
+        self.encode().is_empty()
+    }
+
+    /// Low 160 bits of keccak256(extension), which the order salt must carry when an extension is set
+    pub fn hash_low_160(&self) -> U256 {
+        let hash = U256::from_big_endian(&keccak256(self.encode()));
+        hash & ((U256::one() << 160) - 1)
+    }
+
+    /// Whether `salt` commits to this extension
+    pub fn matches_salt(&self, salt: U256) -> bool {
+        salt & ((U256::one() << 160) - 1) == self.hash_low_160()
+    }
+
+    /// Getter contract and its data for making amounts, if the order overrides them
+    pub fn making_amount_getter(&self) -> Option<(Address, Bytes)> {
+        split_target(&self.making_amount_data)
+    }
+
+    /// Getter contract and its data for taking amounts, if the order overrides them
+    pub fn taking_amount_getter(&self) -> Option<(Address, Bytes)> {
+        split_target(&self.taking_amount_data)
+    }
+
+    pub fn pre_interaction(&self) -> Option<(Address, Bytes)> {
+        split_target(&self.pre_interaction_data)
+    }
+
+    pub fn post_interaction(&self) -> Option<(Address, Bytes)> {
+        split_target(&self.post_interaction_data)
+    }
+
+    /// Log the layout of each non-empty field
+    pub fn log_summary(&self) {
+        info!("🧩 Order extension ({} bytes):", self.encode().len());
+        let named = [
+            ("MakerAssetSuffix", &self.maker_asset_suffix),
+            ("TakerAssetSuffix", &self.taker_asset_suffix),
+            ("MakingAmountData", &self.making_amount_data),
+            ("TakingAmountData", &self.taking_amount_data),
+            ("Predicate", &self.predicate),
+            ("MakerPermit", &self.maker_permit),
+            ("PreInteractionData", &self.pre_interaction_data),
+            ("PostInteractionData", &self.post_interaction_data),
+            ("CustomData", &self.custom_data),
+        ];
+        for (name, field) in named.iter().filter(|(_, f)| !f.is_empty()) {
+            match split_target(field) {
+                Some((target, data)) if *name != "Predicate" && *name != "CustomData" => {
+                    info!("  {}: {} bytes, target {:?}, data {}", name, field.len(), target, data)
+                }
+                _ => info!("  {}: {} bytes", name, field.len()),
+            }
+        }
+    }
+
+    fn offset_fields(&self) -> [&Bytes; OFFSET_FIELDS] {
+        [
+            &self.maker_asset_suffix,
+            &self.taker_asset_suffix,
+            &self.making_amount_data,
+            &self.taking_amount_data,
+            &self.predicate,
+            &self.maker_permit,
+            &self.pre_interaction_data,
+            &self.post_interaction_data,
+        ]
+    }
+}
+
+impl FromStr for OrderExtension {
+    type Err = eyre::Report;
+
+    /// Parse hex, with or without 0x
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(s))
+            .map_err(|e| eyre::eyre!("Failed to decode extension: {}", e))?;
+        Self::decode(&bytes)
+    }
+}
+
+/// `[20-byte address][data]`, None when shorter than an address
+fn split_target(field: &[u8]) -> Option<(Address, Bytes)> {
+    (field.len() >= 20).then(|| (Address::from_slice(&field[..20]), Bytes::from(field[20..].to_vec())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::simple_config::MevConfig;
+
+    fn captured() -> MevConfig {
+        serde_json::from_str(include_str!("../../mev_data_updated.json")).unwrap()
+    }
+
+    #[test]
+    fn captured_extensions_round_trip() {
+        for captured in captured().one_inch_orders {
+            let raw: Bytes = captured.order.extension.parse().unwrap();
+            let extension = OrderExtension::decode(&raw).unwrap();
+            assert_eq!(extension.encode(), raw);
+            assert_eq!(extension.making_amount_data.len(), 0x25);
+            assert_eq!(extension.taking_amount_data.len(), 0x25);
+            assert_eq!(extension.post_interaction_data.len(), 0x4e);
+            assert!(extension.matches_salt(U256::from_dec_str(&captured.order.salt).unwrap()));
+        }
+    }
+
+    #[test]
+    fn all_zero_header_round_trips() {
+        let raw = [0u8; HEADER_LENGTH];
+        let extension = OrderExtension::decode(&raw).unwrap();
+        assert_eq!(extension.encode().as_ref(), raw);
+        assert!(!extension.is_empty());
+        assert!(OrderExtension::decode(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn changed_fields_are_re_encoded() {
+        let raw: Bytes = captured().one_inch_orders[0].order.extension.parse().unwrap();
+        let mut extension = OrderExtension::decode(&raw).unwrap();
+        extension.predicate = Bytes::from(vec![0xab; 4]);
+
+        let encoded = extension.encode();
+        assert_eq!(encoded.len(), raw.len() + 4);
+        let decoded = OrderExtension::decode(&encoded).unwrap();
+        assert_eq!(decoded.predicate, extension.predicate);
+        assert_eq!(decoded.post_interaction_data, extension.post_interaction_data);
+        assert_eq!(decoded.encode(), encoded);
+    }
+
+    #[test]
+    fn rejects_offsets_past_the_body() {
+        let mut raw = vec![0u8; HEADER_LENGTH];
+        raw[31] = 4;
+        assert!(OrderExtension::decode(&raw).is_err());
+        assert!(OrderExtension::decode(&raw[..31]).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use std::fmt;
+use std::str::FromStr;
+use ethers::types::{Address, U256};
+use eyre::Result;
+
+use crate::bindings::aggregation_router_v6::Order;
+use crate::revert::SimulationError;
+
+// Flag bits, as in MakerTraitsLib (limit order protocol v4)
+const NO_PARTIAL_FILLS_FLAG: usize = 255;
+const ALLOW_MULTIPLE_FILLS_FLAG: usize = 254;
+const PRE_INTERACTION_CALL_FLAG: usize = 252;
+const POST_INTERACTION_CALL_FLAG: usize = 251;
+const NEED_CHECK_EPOCH_MANAGER_FLAG: usize = 250;
+const HAS_EXTENSION_FLAG: usize = 249;
+const USE_PERMIT2_FLAG: usize = 248;
+const UNWRAP_WETH_FLAG: usize = 247;
+
+// Low 200 bits: uint80 allowedSender | uint40 expiration | uint40 nonceOrEpoch | uint40 series
+const ALLOWED_SENDER_BITS: usize = 80;
+const EXPIRATION_OFFSET: usize = 80;
+const NONCE_OR_EPOCH_OFFSET: usize = 120;
+const SERIES_OFFSET: usize = 160;
+const UINT40_BITS: usize = 40;
+
+/// `makerTraits` of a 1inch V6 order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MakerTraits(pub U256);
+
+impl MakerTraits {
+    pub fn raw(&self) -> U256 {
+        self.0
+    }
+
+    /// Low 80 bits of the only address allowed to fill, zero when anyone can
+    pub fn allowed_sender(&self) -> u128 {
+        self.bits(0, ALLOWED_SENDER_BITS).as_u128()
+    }
+
+    pub fn is_allowed_sender(&self, sender: Address) -> bool {
+        let allowed = self.allowed_sender();
+        allowed == 0 || allowed == low_80_bits(sender)
+    }
+
+    /// Unix timestamp after which the order can't be filled, zero for never
+    pub fn expiration(&self) -> u64 {
+        self.bits(EXPIRATION_OFFSET, UINT40_BITS).as_u64()
+    }
+
+    /// Same rule as the router: expired once `expiration < block.timestamp`
+    pub fn is_expired(&self, timestamp: u64) -> bool {
+        let expiration = self.expiration();
+        expiration != 0 && expiration < timestamp
+    }
+
+    /// Bit invalidator nonce, or the epoch when the epoch manager is checked
+    pub fn nonce_or_epoch(&self) -> u64 {
+        self.bits(NONCE_OR_EPOCH_OFFSET, UINT40_BITS).as_u64()
+    }
+
+    pub fn series(&self) -> u64 {
+        self.bits(SERIES_OFFSET, UINT40_BITS).as_u64()
+    }
+
+    pub fn allow_partial_fills(&self) -> bool {
+        !self.0.bit(NO_PARTIAL_FILLS_FLAG)
+    }
+
+    pub fn allow_multiple_fills(&self) -> bool {
+        self.0.bit(ALLOW_MULTIPLE_FILLS_FLAG)
+    }
+
+    pub fn need_pre_interaction_call(&self) -> bool {
+        self.0.bit(PRE_INTERACTION_CALL_FLAG)
+    }
+
+    pub fn need_post_interaction_call(&self) -> bool {
+        self.0.bit(POST_INTERACTION_CALL_FLAG)
+    }
+
+    pub fn need_check_epoch_manager(&self) -> bool {
+        self.0.bit(NEED_CHECK_EPOCH_MANAGER_FLAG)
+    }
+
+    pub fn has_extension(&self) -> bool {
+        self.0.bit(HAS_EXTENSION_FLAG)
+    }
+
+    pub fn use_permit2(&self) -> bool {
+        self.0.bit(USE_PERMIT2_FLAG)
+    }
+
+    pub fn unwrap_weth(&self) -> bool {
+        self.0.bit(UNWRAP_WETH_FLAG)
+    }
+
+    /// Orders that are single-fill or non-partial are invalidated through the bit invalidator,
+    /// everything else through the remaining-amount invalidator
+    pub fn use_bit_invalidator(&self) -> bool {
+        !self.allow_partial_fills() || !self.allow_multiple_fills()
+    }
+
+    /// Restrict fills to `sender` (only its low 80 bits are stored)
+    pub fn with_allowed_sender(self, sender: Address) -> Self {
+        self.with_bits(0, ALLOWED_SENDER_BITS, U256::from(low_80_bits(sender)))
+    }
+
+    pub fn with_expiration(self, expiration: u64) -> Self {
+        self.with_bits(EXPIRATION_OFFSET, UINT40_BITS, U256::from(expiration))
+    }
+
+    pub fn with_nonce_or_epoch(self, nonce_or_epoch: u64) -> Self {
+        self.with_bits(NONCE_OR_EPOCH_OFFSET, UINT40_BITS, U256::from(nonce_or_epoch))
+    }
+
+    pub fn with_series(self, series: u64) -> Self {
+        self.with_bits(SERIES_OFFSET, UINT40_BITS, U256::from(series))
+    }
+
+    pub fn with_partial_fills(self, allow: bool) -> Self {
+        self.with_flag(NO_PARTIAL_FILLS_FLAG, !allow)
+    }
+
+    pub fn with_multiple_fills(self, allow: bool) -> Self {
+        self.with_flag(ALLOW_MULTIPLE_FILLS_FLAG, allow)
+    }
+
+    pub fn with_pre_interaction_call(self, enabled: bool) -> Self {
+        self.with_flag(PRE_INTERACTION_CALL_FLAG, enabled)
+    }
+
+    pub fn with_post_interaction_call(self, enabled: bool) -> Self {
+        self.with_flag(POST_INTERACTION_CALL_FLAG, enabled)
+    }
+
+    pub fn with_check_epoch_manager(self, enabled: bool) -> Self {
+        self.with_flag(NEED_CHECK_EPOCH_MANAGER_FLAG, enabled)
+    }
+
+    pub fn with_extension(self, enabled: bool) -> Self {
+        self.with_flag(HAS_EXTENSION_FLAG, enabled)
+    }
+
+    pub fn with_permit2(self, enabled: bool) -> Self {
+        self.with_flag(USE_PERMIT2_FLAG, enabled)
+    }
+
+    pub fn with_unwrap_weth(self, enabled: bool) -> Self {
+        self.with_flag(UNWRAP_WETH_FLAG, enabled)
+    }
+
+    fn bits(&self, offset: usize, width: usize) -> U256 {
+        (self.0 >> offset) & ((U256::one() << width) - 1)
+    }
+
+    fn with_bits(self, offset: usize, width: usize, value: U256) -> Self {
+        let mask = ((U256::one() << width) - 1) << offset;
+        MakerTraits((self.0 & !mask) | ((value << offset) & mask))
+    }
+
+    fn with_flag(self, bit: usize, enabled: bool) -> Self {
+        let flag = U256::one() << bit;
+        MakerTraits(if enabled { self.0 | flag } else { self.0 & !flag })
+    }
+}
+
+impl From<U256> for MakerTraits {
+    fn from(raw: U256) -> Self {
+        MakerTraits(raw)
+    }
+}
+
+impl FromStr for MakerTraits {
+    type Err = eyre::Report;
+
+    /// Accepts 0x-prefixed hex (as in MEV data) or decimal
+    fn from_str(s: &str) -> Result<Self> {
+        let raw = match s.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16)?,
+            None => U256::from_dec_str(s)?,
+        };
+        Ok(MakerTraits(raw))
+    }
+}
+
+impl fmt::Display for MakerTraits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "allowedSender=0x{:020x} expiration={} nonceOrEpoch={} series={} partialFills={} multipleFills={} \
+             preInteraction={} postInteraction={} checkEpochManager={} hasExtension={} permit2={} unwrapWeth={}",
+            self.allowed_sender(),
+            self.expiration(),
+            self.nonce_or_epoch(),
+            self.series(),
+            self.allow_partial_fills(),
+            self.allow_multiple_fills(),
+            self.need_pre_interaction_call(),
+            self.need_post_interaction_call(),
+            self.need_check_epoch_manager(),
+            self.has_extension(),
+            self.use_permit2(),
+            self.unwrap_weth(),
+        )
+    }
+}
+
+/// Checks that need nothing but the order, the fill request and the fork block timestamp
+///
+/// Returns every reason the router would reject the fill. The partial-fill check assumes the
+/// default proportional amount getters, so it only covers orders without an extension when
+/// `amount` is a taking amount.
+pub fn preflight_check(
+    order: &Order,
+    amount: U256,
+    is_making_amount: bool,
+    taker: Address,
+    remaining: U256,
+    block_timestamp: u64,
+) -> Vec<SimulationError> {
+    let traits = MakerTraits(order.maker_traits);
+    let mut issues = Vec::new();
+
+    if !traits.is_allowed_sender(taker) {
+        issues.push(SimulationError::PrivateOrder);
+    }
+    if traits.is_expired(block_timestamp) {
+        issues.push(SimulationError::OrderExpired);
+    }
+
+    if !traits.allow_partial_fills() {
+        let making_amount = if is_making_amount {
+            Some(amount)
+        } else if !traits.has_extension() && !order.taking_amount.is_zero() {
+            amount.checked_mul(order.making_amount).map(|product| product / order.taking_amount)
+        } else {
+            None
+        };
+        // Like `_fill`, cap the making amount at what is left before comparing
+        if making_amount.is_some_and(|making| making.min(remaining) != order.making_amount) {
+            issues.push(SimulationError::PartialFillNotAllowed);
+        }
+    }
+
+    issues
+}
+
+fn low_80_bits(address: Address) -> u128 {
+    let bytes = address.as_bytes();
+    let mut low = [0u8; 16];
+    low[6..].copy_from_slice(&bytes[10..]);
+    u128::from_be_bytes(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(maker_traits: MakerTraits) -> Order {
+        Order {
+            salt: U256::zero(),
+            maker: U256::zero(),
+            receiver: U256::zero(),
+            maker_asset: U256::zero(),
+            taker_asset: U256::zero(),
+            making_amount: U256::from(1000),
+            taking_amount: U256::from(2000),
+            maker_traits: maker_traits.raw(),
+        }
+    }
+
+    fn partial_fill_rejected(order: &Order, amount: U256, is_making_amount: bool, remaining: U256) -> bool {
+        preflight_check(order, amount, is_making_amount, Address::zero(), remaining, 0).contains(&SimulationError::PartialFillNotAllowed)
+    }
+
+    #[test]
+    fn whole_fill_only_orders_clamp_to_remaining() {
+        let order = order(MakerTraits::default().with_partial_fills(false));
+        let remaining = order.making_amount;
+        assert!(!partial_fill_rejected(&order, U256::from(2000), false, remaining));
+        // Asking for more than is left is capped, like `_fill` does
+        assert!(!partial_fill_rejected(&order, U256::from(5000), false, remaining));
+        assert!(!partial_fill_rejected(&order, U256::from(1500), true, remaining));
+        assert!(partial_fill_rejected(&order, U256::from(1999), false, remaining));
+        assert!(partial_fill_rejected(&order, U256::from(999), true, remaining));
+    }
+
+    #[test]
+    fn overflowing_taking_amount_does_not_panic() {
+        let order = order(MakerTraits::default().with_partial_fills(false));
+        assert!(!partial_fill_rejected(&order, U256::MAX, false, order.making_amount));
+    }
+
+    #[test]
+    fn partial_fills_allowed() {
+        let order = order(MakerTraits::default());
+        assert!(!partial_fill_rejected(&order, U256::one(), false, order.making_amount));
+    }
+}
@@ -0,0 +1,282 @@
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::{
+    providers::Middleware,
+    types::{Address, BlockNumber, Bytes, U256},
+};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::anvil_setup::{SignerClient, approve_token_from};
+use crate::bindings::aggregation_router_v6::{AggregationRouterV6, Order};
+use crate::config::simple_config::OneInchOrder;
+use crate::funding::fund_account;
+use crate::revert::SimulationError;
+use super::access::{access_requirements, apply_taker_setup, resolve_taker};
+use super::amount_getters::{AmountGetter, confirm_calculators};
+use super::extension::OrderExtension;
+use super::maker_traits::MakerTraits;
+use super::order_status::{OrderStatus, order_status};
+use super::signature::{SignatureCheck, order_hash, verify_order_signature};
+use super::taker_traits::TakerTraits;
+use super::{AGGREGATION_ROUTER_V6, block_timestamp, parse_contract_signature, parse_order, parse_signature, unpack_address};
+
+/// Stop searching once the bracket is this many basis points of the upper bound
+const SEARCH_PRECISION_BPS: u64 = 1;
+/// Upper bound on `eth_call`s per search
+const MAX_PROBES: u32 = 64;
+
+/// Largest taking amount a fill can request without reverting
+#[derive(Debug, Clone)]
+pub struct MaxFill {
+    /// Taking amount passed to `fillOrderArgs`
+    pub amount: U256,
+    pub making_amount: U256,
+    pub taking_amount: U256,
+    /// Taker asset paid per maker asset received, in raw units
+    pub effective_price: f64,
+    /// Remaining making amount on the fork when the search ran
+    pub remaining: U256,
+    pub probes: u32,
+    /// Why the next larger amount reverted, None when the upper bound filled
+    pub limit: Option<SimulationError>,
+}
+
+impl MaxFill {
+    pub fn log_summary(&self) {
+        info!("📈 Max fillable amount after {} probe(s):", self.probes);
+        info!("  Taking amount requested: {} wei", self.amount);
+        info!("  Making Amount: {} wei (of {} remaining)", self.making_amount, self.remaining);
+        info!("  Taking Amount: {} wei", self.taking_amount);
+        info!("  Effective price: {:.8} taker/maker", self.effective_price);
+        match &self.limit {
+            Some(limit) => info!("  Limited by: {}", limit),
+            None => info!("  Limited by: remaining amount"),
+        }
+    }
+}
+
+/// Binary-search the largest taking amount `fillOrderArgs` accepts on the fork
+///
+/// Funds and approves the taker for the upper bound, so run it on an isolated snapshot
+/// (`ForkSession::with_isolated`). Each probe is an `eth_call`; remaining amount, threshold,
+/// predicate and maker balance/allowance all show up as reverts. Returns None when the order
+/// has nothing left to fill or no amount fills.
+pub async fn find_max_fillable(order_config: &OneInchOrder, extension_data: &str, client: &Arc<SignerClient>) -> Result<Option<MaxFill>> {
+    info!("🔍 Searching max fillable amount...");
+    let router = AggregationRouterV6::new(Address::from_str(AGGREGATION_ROUTER_V6)?, client.clone());
+
+    let order = parse_order(order_config)?;
+    let (r, vs) = parse_signature(order_config)?;
+    let extension = OrderExtension::from_str(extension_data)?;
+    confirm_calculators(client, &order, &extension).await?;
+    let (taker_traits, args) = TakerTraits::new().extension(extension.encode()).encode()?;
+
+    let chain_id = client.get_chainid().await?.as_u64();
+    let hash = order_hash(&order, chain_id, router.address());
+    let timestamp = block_timestamp(client, BlockNumber::Latest).await?;
+    let remaining = match order_status(client, &order, hash, timestamp).await? {
+        OrderStatus::Fillable(remaining) => remaining,
+        status => {
+            warn!("⛔ Order is {}, nothing to fill", status);
+            return Ok(None);
+        }
+    };
+
+    let Some(upper) = taking_upper_bound(&order, &extension, remaining, timestamp) else {
+        warn!("⚠️  Can't bound the taking amount for this order");
+        return Ok(None);
+    };
+    info!("  Remaining making amount: {}, taking amount upper bound: {}", remaining, upper);
+
+    let taker = resolve_taker(client, order_config.taker_setup.as_ref(), &order).await?;
+    apply_taker_setup(client, taker, order_config.taker_setup.as_ref(), &access_requirements(&order, &extension)).await?;
+    let taker_asset = unpack_address(order.taker_asset);
+    fund_account(client, taker_asset, taker, upper, &order_config.funding.clone().unwrap_or_default()).await?;
+    approve_token_from(client, taker_asset, taker, router.address(), upper).await?;
+
+    let signature = parse_contract_signature(order_config)?;
+    let contract_signature = match verify_order_signature(client, unpack_address(order.maker), hash, r, vs, &signature).await? {
+        SignatureCheck::Erc1271 => Some(signature),
+        _ => None,
+    };
+    let probe = Probe { router: &router, taker, order: &order, r, vs, contract_signature, taker_traits, args: &args };
+    let allow_partial = MakerTraits(order.maker_traits).allow_partial_fills();
+    let search = bisect(upper, allow_partial, |amount| probe.fill(amount)).await;
+    let reason = search.limit.as_ref().map(ToString::to_string).unwrap_or_default();
+    let Some((amount, fill)) = search.best else {
+        // Non-partial orders fill all or nothing, so there is nothing to search
+        if !allow_partial {
+            warn!("⛔ Full fill reverted and the order does not allow partial fills: {}", reason);
+        } else {
+            warn!("⛔ No fill amount succeeded after {} probe(s): {}", search.probes, reason);
+        }
+        return Ok(None);
+    };
+    let result = max_fill(amount, fill, remaining, search.probes, search.limit);
+    result.log_summary();
+    Ok(Some(result))
+}
+
+/// Outcome of `bisect`
+struct Search<T, E> {
+    /// Largest accepted amount and what the probe returned for it
+    best: Option<(U256, T)>,
+    /// Why the smallest rejected amount failed, None when `upper` was accepted
+    limit: Option<E>,
+    probes: u32,
+}
+
+/// Largest amount in `0..=upper` that `probe` accepts, to within `SEARCH_PRECISION_BPS` of
+/// `upper`; `upper` is probed first and, without partial fills, is the only probe
+async fn bisect<T, E, F, Fut>(upper: U256, allow_partial: bool, mut probe: F) -> Search<T, E>
+where
+    F: FnMut(U256) -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let mut search = Search { best: None, limit: None, probes: 1 };
+    match probe(upper).await {
+        Ok(fill) => {
+            search.best = Some((upper, fill));
+            return search;
+        }
+        Err(error) => search.limit = Some(error),
+    }
+    if !allow_partial {
+        return search;
+    }
+
+    let precision = (upper * SEARCH_PRECISION_BPS / 10_000).max(U256::one());
+    let (mut low, mut high) = (U256::zero(), upper);
+    while high - low > precision && search.probes < MAX_PROBES {
+        let mid = low + (high - low) / 2;
+        search.probes += 1;
+        match probe(mid).await {
+            Ok(fill) => {
+                low = mid;
+                search.best = Some((mid, fill));
+            }
+            Err(error) => {
+                high = mid;
+                search.limit = Some(error);
+            }
+        }
+    }
+    search
+}
+
+/// Taking amount that buys the whole remaining making amount, with the getter the router
+/// would use; unknown getters fall back to the order's own ratio with a 2x margin
+fn taking_upper_bound(order: &Order, extension: &OrderExtension, remaining: U256, timestamp: u64) -> Option<U256> {
+    let getter = AmountGetter::from_field(&extension.taking_amount_data);
+    match getter.taking_amount(order, remaining, remaining, timestamp) {
+        Some(taking) => Some(taking),
+        None => AmountGetter::Proportional.taking_amount(order, remaining, remaining, timestamp)?.checked_mul(2.into()),
+    }
+}
+
+fn max_fill(amount: U256, (making_amount, taking_amount): (U256, U256), remaining: U256, probes: u32, limit: Option<SimulationError>) -> MaxFill {
+    let making = making_amount.to_string().parse::<f64>().unwrap_or(0.0);
+    let taking = taking_amount.to_string().parse::<f64>().unwrap_or(0.0);
+    MaxFill {
+        amount,
+        making_amount,
+        taking_amount,
+        effective_price: if making > 0.0 { taking / making } else { 0.0 },
+        remaining,
+        probes,
+        limit,
+    }
+}
+
+/// One `fillOrderArgs` call shape, probed with different amounts
+struct Probe<'a> {
+    router: &'a AggregationRouterV6<SignerClient>,
+    /// Sender of the probes, the signer or an impersonated account
+    taker: Address,
+    order: &'a Order,
+    r: [u8; 32],
+    vs: [u8; 32],
+    /// Signature of an ERC-1271 maker, probed through fillContractOrderArgs
+    contract_signature: Option<Bytes>,
+    taker_traits: U256,
+    args: &'a Bytes,
+}
+
+impl Probe<'_> {
+    /// `(making, taking)` the router returns for `amount`, or why it reverted
+    async fn fill(&self, amount: U256) -> std::result::Result<(U256, U256), SimulationError> {
+        let call = match &self.contract_signature {
+            Some(signature) => self.router.fill_contract_order_args(self.order.clone(), signature.clone(), amount, self.taker_traits, self.args.clone()),
+            None => self.router.fill_order_args(self.order.clone(), self.r, self.vs, amount, self.taker_traits, self.args.clone()),
+        }.from(self.taker);
+        match call.call().await {
+            Ok((making, taking, _hash)) => {
+                info!("  ✅ {} -> making {}, taking {}", amount, making, taking);
+                Ok((making, taking))
+            }
+            Err(e) => {
+                let error = SimulationError::from_report(&e.into());
+                info!("  ❌ {} -> {}", amount, error);
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::{ready, Ready};
+
+    /// Probe that fills every amount up to `limit`
+    fn up_to(limit: Option<u64>) -> impl FnMut(U256) -> Ready<std::result::Result<U256, U256>> {
+        move |amount| ready(match limit {
+            Some(limit) if amount <= limit.into() => Ok(amount),
+            _ => Err(amount),
+        })
+    }
+
+    #[tokio::test]
+    async fn nothing_fillable() {
+        let search = bisect(1_000.into(), true, up_to(None)).await;
+        assert!(search.best.is_none());
+        assert_eq!(search.limit, Some(U256::one()));
+        assert_eq!(search.probes, 10);
+    }
+
+    #[tokio::test]
+    async fn fully_fillable_takes_one_probe() {
+        let search = bisect(1_000.into(), true, up_to(Some(1_000))).await;
+        assert_eq!(search.best, Some((1_000.into(), 1_000.into())));
+        assert!(search.limit.is_none());
+        assert_eq!(search.probes, 1);
+    }
+
+    #[tokio::test]
+    async fn converges_on_the_boundary() {
+        for limit in [1, 499, 500, 637, 998, 999] {
+            let search = bisect(1_000.into(), true, up_to(Some(limit))).await;
+            assert_eq!(search.best.map(|(amount, _)| amount), Some(limit.into()), "limit {}", limit);
+            assert_eq!(search.limit, Some(U256::from(limit + 1)), "limit {}", limit);
+        }
+    }
+
+    #[tokio::test]
+    async fn coarse_precision_on_large_bounds() {
+        let upper = U256::exp10(24);
+        let limit = U256::exp10(23) * 3 + 12_345;
+        let search = bisect(upper, true, |amount| ready(if amount <= limit { Ok(()) } else { Err(()) })).await;
+        let (amount, _) = search.best.unwrap();
+        assert!(amount <= limit && limit - amount <= upper * SEARCH_PRECISION_BPS / 10_000);
+        assert!(search.probes <= MAX_PROBES);
+    }
+
+    #[tokio::test]
+    async fn whole_orders_are_probed_once() {
+        let search = bisect(1_000.into(), false, up_to(Some(500))).await;
+        assert!(search.best.is_none());
+        assert_eq!((search.limit, search.probes), (Some(1_000.into()), 1));
+    }
+}
@@ -0,0 +1,743 @@
+pub mod access;
+pub mod amount_getters;
+pub mod batch;
+pub mod extension;
+pub mod maker_traits;
+pub mod max_fill;
+pub mod native;
+pub mod order_builder;
+pub mod order_status;
+pub mod permit;
+pub mod predicate;
+pub mod signature;
+pub mod solvency;
+pub mod taker_interaction;
+pub mod taker_traits;
+
+use std::sync::Arc;
+use std::time::Instant;
+use ethers::{
+    providers::Middleware,
+    types::{Address, BlockNumber, Bytes, H256, U256},
+};
+use eyre::Result;
+use tracing::{info, warn, error};
+use std::str::FromStr;
+
+use crate::config::simple_config::{AllowanceStrategy, ComparisonResult, OneInchOrder, FundingStrategy};
+use crate::anvil_setup::{SignerClient, get_token_balance, approve_token_from};
+use crate::funding::fund_account;
+use crate::execution::{ExecutionMode, ExecutionReceipt, BalanceWatch, execute_call};
+use crate::simulation::{SimulationInputs, SimulationOutcome};
+use crate::revert::SimulationError;
+use crate::bindings::aggregation_router_v6::{AggregationRouterV6, Order};
+use crate::bindings::permit_and_call::PermitAndCallFill;
+use access::{access_requirements, apply_taker_setup, resolve_taker};
+use amount_getters::{ExpectedFill, confirm_calculators};
+use extension::OrderExtension;
+use maker_traits::{MakerTraits, preflight_check};
+use native::{NativeFlow, fund_native};
+use order_status::{OrderStatus, order_status};
+use permit::grant_allowance;
+use predicate::{Predicate, check_predicate};
+use signature::{SignatureCheck, order_hash, verify_order_signature};
+use solvency::check_maker_solvency;
+use taker_interaction::resolve_taker_interaction;
+use taker_traits::TakerTraits;
+
+/// AggregationRouterV6 on BSC, also the EIP-712 verifying contract for orders
+pub const AGGREGATION_ROUTER_V6: &str = "0x111111125421ca6dc452d289314280a0f8842a65";
+
+/// Uniswap Permit2, used for maker transfers when USE_PERMIT2 is set
+pub const PERMIT2: &str = "0x000000000022d473030f116ddee9f6b43ac78ba3";
+
+/// Wrapped BNB, the router's `_WETH`: the only asset it wraps or unwraps
+pub const WBNB: &str = "0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c";
+
+pub async fn fill_order_args(order_config: &OneInchOrder, extension_data: &str, client: &Arc<SignerClient>, mode: ExecutionMode) -> Result<SimulationOutcome> {
+    info!("🔄 Executing 1inch order fill simulation ({})...", mode);
+    let started = Instant::now();
+
+    let router_contract = setup_oneinch_contract(client).await?;
+
+    let order = parse_order(order_config)?;
+    let (r, vs) = parse_signature(order_config)?;
+    let amount = U256::from_dec_str(&order_config.amount)?;
+
+    // Parse extension data
+    let extension = OrderExtension::from_str(extension_data)?;
+    extension.log_summary();
+    if !extension.is_empty() && !extension.matches_salt(order.salt) {
+        warn!("⚠️  Order salt does not commit to keccak256(extension), the router will reject it");
+    }
+    confirm_calculators(client, &order, &extension).await?;
+
+    let allowance = order_config.taker_allowance.unwrap_or_default();
+    let mut taker_traits = TakerTraits::new().extension(extension.encode());
+    if allowance == AllowanceStrategy::Permit2 {
+        taker_traits = taker_traits.use_permit2();
+    }
+    if order_config.unwrap_native {
+        taker_traits = taker_traits.unwrap_weth();
+    }
+    let (mut built_taker_traits, mut built_args) = taker_traits.encode()?;
+
+    let mut outcome = SimulationOutcome::new("1inch", mode, fill_inputs(&order, amount));
+    let taker = resolve_taker(client, order_config.taker_setup.as_ref(), &order).await?;
+    let Some((remaining, signature)) = preflight(client, order_config, &order, amount, built_taker_traits, taker, &mut outcome).await? else {
+        return Ok(outcome.finish(started));
+    };
+    check_captured_remaining(order_config, remaining);
+    let amount = clamp_to_remaining(amount, remaining, built_taker_traits);
+    if !check_order_predicate(client, &extension, &mut outcome).await? {
+        return Ok(outcome.finish(started));
+    }
+    if !check_solvency(client, &order, &extension, amount, built_taker_traits, remaining, &mut outcome).await? {
+        return Ok(outcome.finish(started));
+    }
+
+    // The interaction only adds a target and interaction bytes, so the checks above still hold
+    let funding = order_config.funding.clone().unwrap_or_default();
+    let mut self_funded = false;
+    if let Some(interaction) = &order_config.taker_interaction {
+        let timestamp = block_timestamp(client, BlockNumber::Latest).await?;
+        let is_making_amount = TakerTraits::is_making_amount(built_taker_traits);
+        let expected = ExpectedFill::compute(&order, &extension, amount, is_making_amount, remaining, timestamp)
+            .ok_or_else(|| eyre::eyre!("Can't price the fill off-chain for the taker interaction (unknown amount getter)"))?;
+        let resolved = resolve_taker_interaction(client, interaction, &order, taker, expected.making_amount, expected.taking_amount).await?;
+        info!("🤝 Taker interaction on {:?}, maker asset to {:?}", resolved.target, resolved.receiver.unwrap_or(taker));
+        (built_taker_traits, built_args) = resolved.apply(taker_traits).encode()?;
+        self_funded = resolved.self_funded;
+    }
+
+    let msg_value = native_msg_value(client, order_config, &order, &extension, amount, built_taker_traits, remaining).await?;
+    let native = NativeFlow::detect(&order, built_taker_traits, msg_value)?;
+
+    apply_taker_setup(client, taker, order_config.taker_setup.as_ref(), &access_requirements(&order, &extension)).await?;
+
+    let request = FillRequest {
+        order: order.clone(),
+        taker,
+        r, vs,
+        contract_signature: (signature == SignatureCheck::Erc1271).then(|| parse_contract_signature(order_config)).transpose()?,
+        amount,
+        taker_traits: built_taker_traits,
+        args: built_args,
+        funding: (!self_funded).then_some(&funding),
+        allowance,
+        native,
+        mode,
+    };
+    let method = match request.contract_signature.is_some() {
+        true => "fillContractOrderArgs",
+        false => "fillOrderArgs",
+    };
+    let result = execute_fill_order_args(client, &router_contract, request).await?;
+    record_fill(&mut outcome, method, result);
+    compare_expected_fill(client, &order, &extension, amount, built_taker_traits, remaining, &mut outcome).await?;
+
+    Ok(outcome.finish(started))
+}
+
+
+
+pub async fn fill_order(order_config: &OneInchOrder, _extension_data: &str, client: &Arc<SignerClient>, mode: ExecutionMode) -> Result<SimulationOutcome> {
+    info!("🔄 Executing 1inch order fill simulation ({})...", mode);
+    let started = Instant::now();
+
+    let router_contract = setup_oneinch_contract(client).await?;
+
+    let order = parse_order(order_config)?;
+    let (r, vs) = parse_signature(order_config)?;
+    let amount = U256::from_dec_str(&order_config.amount)?;
+
+    let mut taker_traits = TakerTraits::new();
+    if order_config.unwrap_native {
+        taker_traits = taker_traits.unwrap_weth();
+    }
+    let built_taker_traits = taker_traits.traits()?;
+
+    let mut outcome = SimulationOutcome::new("1inch", mode, fill_inputs(&order, amount));
+    let taker = resolve_taker(client, order_config.taker_setup.as_ref(), &order).await?;
+    let Some((remaining, signature)) = preflight(client, order_config, &order, amount, built_taker_traits, taker, &mut outcome).await? else {
+        return Ok(outcome.finish(started));
+    };
+    check_captured_remaining(order_config, remaining);
+    let amount = clamp_to_remaining(amount, remaining, built_taker_traits);
+    if !check_solvency(client, &order, &OrderExtension::default(), amount, built_taker_traits, remaining, &mut outcome).await? {
+        return Ok(outcome.finish(started));
+    }
+
+    let msg_value = native_msg_value(client, order_config, &order, &OrderExtension::default(), amount, built_taker_traits, remaining).await?;
+    let native = NativeFlow::detect(&order, built_taker_traits, msg_value)?;
+
+    apply_taker_setup(client, taker, order_config.taker_setup.as_ref(), &access_requirements(&order, &OrderExtension::default())).await?;
+
+    let funding = order_config.funding.clone().unwrap_or_default();
+    let request = FillRequest {
+        order: order.clone(),
+        taker,
+        r, vs,
+        contract_signature: (signature == SignatureCheck::Erc1271).then(|| parse_contract_signature(order_config)).transpose()?,
+        amount,
+        taker_traits: built_taker_traits,
+        args: Bytes::new(),
+        funding: Some(&funding),
+        allowance: AllowanceStrategy::Approve,
+        native,
+        mode,
+    };
+    let method = match request.contract_signature.is_some() {
+        true => "fillContractOrder",
+        false => "fillOrder",
+    };
+    let result = execute_fill_order_standard(&router_contract, request).await?;
+    record_fill(&mut outcome, method, result);
+    compare_expected_fill(client, &order, &OrderExtension::default(), amount, built_taker_traits, remaining, &mut outcome).await?;
+
+    Ok(outcome.finish(started))
+}
+
+/// Order struct from the decimal fields of an order config (addresses packed as uint256)
+pub(crate) fn parse_order(order_config: &OneInchOrder) -> Result<Order> {
+    Ok(Order {
+        salt: U256::from_dec_str(&order_config.salt)?,
+        maker: U256::from_dec_str(&order_config.maker)?,
+        receiver: U256::from_dec_str(&order_config.receiver)?,
+        maker_asset: U256::from_dec_str(&order_config.maker_asset)?,
+        taker_asset: U256::from_dec_str(&order_config.taker_asset)?,
+        making_amount: U256::from_dec_str(&order_config.making_amount)?,
+        taking_amount: U256::from_dec_str(&order_config.taking_amount)?,
+        maker_traits: U256::from_dec_str(&order_config.maker_traits)?,
+    })
+}
+
+/// Compact `(r, vs)` signature of an order config
+pub(crate) fn parse_signature(order_config: &OneInchOrder) -> Result<([u8; 32], [u8; 32])> {
+    let r = hex::decode(order_config.r.trim_start_matches("0x"))
+        .map_err(|e| eyre::eyre!("Failed to decode r: {}", e))?;
+    let vs = hex::decode(order_config.vs.trim_start_matches("0x"))
+        .map_err(|e| eyre::eyre!("Failed to decode vs: {}", e))?;
+
+    let r: [u8; 32] = r.try_into()
+        .map_err(|_| eyre::eyre!("Invalid r length"))?;
+    let vs: [u8; 32] = vs.try_into()
+        .map_err(|_| eyre::eyre!("Invalid vs length"))?;
+    Ok((r, vs))
+}
+
+/// Signature bytes an ERC-1271 maker validates: the raw captured signature, else `r ++ vs`
+pub(crate) fn parse_contract_signature(order_config: &OneInchOrder) -> Result<Bytes> {
+    match &order_config.signature {
+        Some(signature) => Ok(Bytes::from_str(signature)?),
+        None => {
+            let (r, vs) = parse_signature(order_config)?;
+            Ok(Bytes::from([r, vs].concat()))
+        }
+    }
+}
+
+/// Check the order hash, signature, maker traits and invalidator state before funding anything;
+/// returns the remaining making amount and how the signature checks out, or None (recording the
+/// reasons on `outcome`) when the router would reject the fill from `taker`
+async fn preflight(
+    client: &Arc<SignerClient>,
+    order_config: &OneInchOrder,
+    order: &Order,
+    amount: U256,
+    taker_traits: U256,
+    taker: Address,
+    outcome: &mut SimulationOutcome,
+) -> Result<Option<(U256, SignatureCheck)>> {
+    let mut issues = Vec::new();
+    let (r, vs) = parse_signature(order_config)?;
+
+    let chain_id = client.get_chainid().await?.as_u64();
+    let computed = order_hash(order, chain_id, Address::from_str(AGGREGATION_ROUTER_V6)?);
+    info!("🔏 Order hash: {:?}", computed);
+    if let Some(expected) = order_config.order_hash.as_deref() {
+        let expected = H256::from_str(expected)?;
+        if expected != computed {
+            issues.push(SimulationError::OrderHashMismatch { expected, computed });
+        }
+    }
+
+    let maker = unpack_address(order.maker);
+    let signature = verify_order_signature(client, maker, computed, r, vs, &parse_contract_signature(order_config)?).await?;
+    match signature {
+        SignatureCheck::Invalid { recovered } => {
+            warn!("⚠️  Signature recovers to {:?}, maker is {:?}", recovered, maker);
+            issues.push(SimulationError::BadSignature);
+        }
+        // fillOrder(Args) only recovers ECDSA signatures
+        SignatureCheck::Erc1271 => info!("✅ Signature valid (Erc1271), filling through fillContractOrder(Args)"),
+        check => info!("✅ Signature valid ({:?})", check),
+    }
+
+    let traits = MakerTraits(order.maker_traits);
+    info!("🧾 Maker traits: {}", traits);
+
+    let timestamp = block_timestamp(client, BlockNumber::Latest).await?;
+    let status = order_status(client, order, computed, timestamp).await?;
+    info!("📌 Order status: {}", status);
+
+    let is_making_amount = TakerTraits::is_making_amount(taker_traits);
+    let remaining = status.remaining().unwrap_or(order.making_amount);
+    issues.extend(preflight_check(order, amount, is_making_amount, taker, remaining, timestamp));
+
+    // Expiry is already reported by `preflight_check`
+    if let Some(rejection) = status.rejection(traits).filter(|_| status != OrderStatus::Expired) {
+        issues.push(rejection);
+    }
+
+    if issues.is_empty() {
+        return Ok(status.remaining().map(|remaining| (remaining, signature)));
+    }
+
+    for issue in &issues {
+        warn!("⛔ Pre-flight check failed: {}", issue);
+    }
+    outcome.reject(issues);
+    Ok(None)
+}
+
+/// Evaluate the extension predicate on the fork; returns false (recording `PredicateIsNotTrue`
+/// on `outcome`) when the router would refuse the fill because of it
+async fn check_order_predicate(client: &Arc<SignerClient>, extension: &OrderExtension, outcome: &mut SimulationOutcome) -> Result<bool> {
+    if extension.predicate.is_empty() {
+        return Ok(true);
+    }
+    match Predicate::decode(&extension.predicate) {
+        Ok(predicate) => info!("🔮 Predicate: {}", predicate),
+        Err(e) => warn!("⚠️  Could not decode predicate {}: {}", extension.predicate, e),
+    }
+    if check_predicate(client, &extension.predicate).await? {
+        return Ok(true);
+    }
+
+    warn!("⛔ Predicate is false at the fork block");
+    outcome.reject(vec![SimulationError::PredicateIsNotTrue]);
+    Ok(false)
+}
+
+/// Compare the maker's balance and allowance with the making amount the fill needs; returns
+/// false (recording `MakerInsolvent` on `outcome`) when the maker can't deliver it
+async fn check_solvency(
+    client: &Arc<SignerClient>,
+    order: &Order,
+    extension: &OrderExtension,
+    amount: U256,
+    taker_traits: U256,
+    remaining: U256,
+    outcome: &mut SimulationOutcome,
+) -> Result<bool> {
+    let timestamp = block_timestamp(client, BlockNumber::Latest).await?;
+    let is_making_amount = TakerTraits::is_making_amount(taker_traits);
+    let required = ExpectedFill::compute(order, extension, amount, is_making_amount, remaining, timestamp)
+        .map(|fill| fill.making_amount)
+        .unwrap_or(remaining);
+
+    let solvency = check_maker_solvency(client, order, extension, required, timestamp).await?;
+    if solvency.is_solvent() || solvency.can_change_during_fill {
+        return Ok(true);
+    }
+
+    warn!("⛔ Maker insolvent: {}", solvency);
+    outcome.reject(vec![SimulationError::MakerInsolvent { required, cap: solvency.cap }]);
+    Ok(false)
+}
+
+/// The router fills at most the remaining making amount; clamp making-amount requests to it
+/// (taking-amount requests are re-priced by the router itself)
+fn clamp_to_remaining(amount: U256, remaining: U256, taker_traits: U256) -> U256 {
+    if TakerTraits::is_making_amount(taker_traits) && amount > remaining {
+        warn!("✂️  Clamping making amount {} to the remaining {}", amount, remaining);
+        return remaining;
+    }
+    amount
+}
+
+/// Warn when the captured order disagrees with the fork about what is left to fill
+fn check_captured_remaining(order_config: &OneInchOrder, remaining: U256) {
+    match U256::from_dec_str(&order_config.expected_remaining_amount) {
+        Ok(captured) if captured != remaining => {
+            warn!("⚠️  Captured remaining making amount {} differs from the fork's {}", captured, remaining)
+        }
+        _ => {}
+    }
+}
+
+/// Reproduce the fill amounts off-chain (default pricing or a known amount getter) and
+/// compare the making amount against what the router returned
+async fn compare_expected_fill(
+    client: &Arc<SignerClient>,
+    order: &Order,
+    extension: &OrderExtension,
+    amount: U256,
+    taker_traits: U256,
+    remaining_making_amount: U256,
+    outcome: &mut SimulationOutcome,
+) -> Result<()> {
+    // A sent fill is priced at its own block, a call at the fork head
+    let block = match outcome.receipt.as_ref().and_then(|r| r.block_number) {
+        Some(number) => BlockNumber::Number(number),
+        None => BlockNumber::Latest,
+    };
+    let timestamp = block_timestamp(client, block).await?;
+    let is_making_amount = TakerTraits::is_making_amount(taker_traits);
+
+    let Some(expected) = ExpectedFill::compute(order, extension, amount, is_making_amount, remaining_making_amount, timestamp) else {
+        warn!("⚠️  Can't reproduce the fill amounts off-chain (unknown amount getter)");
+        return Ok(());
+    };
+    expected.log_summary();
+    outcome.inputs.expected_amount_out = Some(expected.making_amount);
+
+    if let (Some(making_amount), Some(taking_amount)) = (outcome.amount_out, outcome.amount_in) {
+        let comparison = compare_amounts(expected.making_amount, making_amount);
+        info!("📊 On-chain vs off-chain fill:");
+        info!("  Making Amount: {} on-chain, {} off-chain ({:.4}% off)", making_amount, expected.making_amount, comparison.difference_pct);
+        info!("  Taking Amount: {} on-chain, {} off-chain", taking_amount, expected.taking_amount);
+        if comparison.is_within_tolerance {
+            info!("✅ Off-chain amounts within tolerance");
+        } else {
+            warn!("⚠️  Off-chain amounts outside tolerance");
+        }
+        outcome.comparison = Some(comparison);
+    }
+    Ok(())
+}
+
+fn compare_amounts(expected: U256, actual: U256) -> ComparisonResult {
+    let expected = expected.to_string().parse::<f64>().unwrap_or(0.0);
+    let actual = actual.to_string().parse::<f64>().unwrap_or(0.0);
+    let difference_pct = if expected > 0.0 {
+        ((actual - expected) / expected * 100.0).abs()
+    } else {
+        0.0
+    };
+
+    ComparisonResult {
+        expected,
+        actual,
+        difference_pct,
+        is_within_tolerance: difference_pct < 1.0, // 1% tolerance, as for swaps
+    }
+}
+
+pub(crate) async fn block_timestamp(client: &Arc<SignerClient>, block: BlockNumber) -> Result<u64> {
+    Ok(client
+        .get_block(block)
+        .await?
+        .map(|block| block.timestamp.as_u64())
+        .unwrap_or_default())
+}
+
+/// Everything one router fill call needs besides the router
+struct FillRequest<'a> {
+    order: Order,
+    /// Sender of the fill, the signer or an impersonated account
+    taker: Address,
+    r: [u8; 32],
+    vs: [u8; 32],
+    /// Signature an ERC-1271 maker accepts; set for contract makers, whose fills go through
+    /// fillContractOrder(Args)
+    contract_signature: Option<Bytes>,
+    amount: U256,
+    taker_traits: U256,
+    /// `fillOrderArgs` args, empty for `fillOrder`
+    args: Bytes,
+    /// How to fund the taker asset, `None` when a taker interaction pays for it
+    funding: Option<&'a FundingStrategy>,
+    allowance: AllowanceStrategy,
+    native: NativeFlow,
+    mode: ExecutionMode,
+}
+
+/// Result of a fill call: `Ok((making, taking, order hash), receipt)` or the call failure
+type FillResult = Result<((U256, U256, [u8; 32]), Option<ExecutionReceipt>)>;
+
+/// `msg.value` for a fill paid in BNB: the taking amount, priced off-chain for making-amount
+/// fills (the router refunds any excess)
+async fn native_msg_value(
+    client: &Arc<SignerClient>,
+    order_config: &OneInchOrder,
+    order: &Order,
+    extension: &OrderExtension,
+    amount: U256,
+    taker_traits: U256,
+    remaining: U256,
+) -> Result<U256> {
+    if !order_config.pay_native {
+        return Ok(U256::zero());
+    }
+    if !TakerTraits::is_making_amount(taker_traits) {
+        return Ok(amount);
+    }
+    let timestamp = block_timestamp(client, BlockNumber::Latest).await?;
+    ExpectedFill::compute(order, extension, amount, true, remaining, timestamp)
+        .map(|expected| expected.taking_amount)
+        .ok_or_else(|| eyre::eyre!("Can't price the taking amount for msg.value (unknown amount getter)"))
+}
+
+/// Native deltas of a sent fill, the taker's net of gas
+fn log_native_deltas(receipt: Option<&ExecutionReceipt>, taker: Address) {
+    for (change, delta) in receipt.map(|r| r.native_deltas(taker)).unwrap_or_default() {
+        info!("  🪙 {} {:?}: {} wei BNB excluding gas", change.label, change.account, delta);
+    }
+}
+
+/// Order fills move the taker asset in and the maker asset out
+fn fill_inputs(order: &Order, amount: U256) -> SimulationInputs {
+    SimulationInputs {
+        token_in: unpack_address(order.taker_asset),
+        token_out: unpack_address(order.maker_asset),
+        amount,
+        expected_amount_out: None,
+    }
+}
+
+fn record_fill(outcome: &mut SimulationOutcome, method: &str, result: FillResult) {
+    outcome.method = Some(method.to_string());
+    match result {
+        Ok(((making_amount, taking_amount, order_hash), receipt)) => {
+            outcome.amount_in = Some(taking_amount);
+            outcome.amount_out = Some(making_amount);
+            outcome.order_hash = Some(H256::from(order_hash));
+            outcome.receipt = receipt;
+        }
+        Err(e) => outcome.revert(e),
+    }
+}
+
+
+/// Execute standard fillOrder (8 fields)
+async fn execute_fill_order_standard(
+    router_contract: &AggregationRouterV6<SignerClient>,
+    request: FillRequest<'_>,
+) -> Result<FillResult> {
+    info!("🔄 Executing fillOrder...");
+    let FillRequest { order, taker, r, vs, contract_signature, amount, taker_traits, funding, native, mode, .. } = request;
+    if contract_signature.is_some() && native.pays_native() {
+        return Err(eyre::eyre!("fillContractOrder is not payable, a contract maker's order can't be paid in BNB"));
+    }
+
+    // Setup taker with required tokens and allowance
+    let client = router_contract.client();
+    
+    // Convert packed addresses back to Address type for balance checks
+    let mut taker_asset_bytes = [0u8; 32];
+    order.taker_asset.to_big_endian(&mut taker_asset_bytes);
+    let mut addr_bytes = [0u8; 20];
+    addr_bytes.copy_from_slice(&taker_asset_bytes[12..32]); // Take last 20 bytes
+    let taker_asset_addr = Address::from(addr_bytes);
+
+    // Check current balance
+    let current_balance = get_token_balance(&client, taker_asset_addr, taker).await?;
+    
+    // We need at least 'amount' tokens to fill the order, or BNB for a native fill
+    if native.pays_native() {
+        fund_native(&client, taker, native.msg_value).await?;
+    } else if let Some(funding) = funding.filter(|_| current_balance < amount) {
+        info!("Insufficient balance. Need {} wei, have {} wei", amount, current_balance);
+        // Set token balance directly using Anvil for any ERC20 token
+        let required_amount: U256 = amount * 2; // Get 2x what we need for safety
+        info!("Setting {} tokens for taker", required_amount.as_u128() as f64 / 1e18);
+
+        // Use Anvil's setBalance to directly give taker the required tokens
+        match fund_account(&client, taker_asset_addr, taker, required_amount, funding).await {
+            Ok(_) => info!("✅ Successfully set token balance for taker"),
+            Err(e) => {
+                warn!("⚠️  Failed to set token balance: {}", e);
+                return Err(e);
+            }
+        }
+    } else {
+        info!("✅ Sufficient balance available");
+    }
+
+    let recheck_current_balance = get_token_balance(&client, taker_asset_addr, taker).await?;
+    info!("Recheck Current taker asset balance: {} wei", recheck_current_balance);
+
+    let allowance_amount: U256 = amount * 10; // Approve 10x for safety
+    if !native.pays_native() {
+        match approve_token_from(&client, taker_asset_addr, taker, router_contract.address(), allowance_amount).await {
+            Ok(_) => info!("Successfully approved 1inch router"),
+            Err(e) => {
+                warn!("Failed to approve router: {}", e);
+                return Err(e);
+            }
+        }
+    }
+    
+    // Debug: Print all parameters before calling
+    info!("  Debug fillOrder parameters:");
+    info!("  Order: {:?}", order);
+    info!("  R: 0x{}", hex::encode(r));
+    info!("  VS: 0x{}", hex::encode(vs));
+    info!("  Amount: {}", amount);
+    info!("  Taker traits: {}", taker_traits);
+
+    let mut watches = fill_balance_watches(&order, taker);
+    watches.extend(native.watches(&order, taker, taker));
+    let call = match contract_signature {
+        Some(signature) => router_contract.fill_contract_order(order, signature, amount, taker_traits),
+        None => router_contract.fill_order(order, r, vs, amount, taker_traits).value(native.msg_value),
+    }.from(taker);
+    let result = execute_call(&client, call, mode, &watches).await;
+
+    match &result {
+        Ok(((actual_making_amount, actual_taking_amount, order_hash), receipt)) => {
+            info!(" Order fill simulation successful!");
+            info!(" Actual Making Amount: {} wei ({:.6} tokens)", 
+                  actual_making_amount, actual_making_amount.as_u128() as f64 / 1e18);
+            info!(" Actual Taking Amount: {} wei ({:.6} tokens)", 
+                  actual_taking_amount, actual_taking_amount.as_u128() as f64 / 1e18);
+            info!(" Order Hash: 0x{}", hex::encode(order_hash));
+            log_native_deltas(receipt.as_ref(), taker);
+        }
+        Err(e) => error!(" Order fill simulation failed: {}", SimulationError::from_report(e)),
+    }
+
+    Ok(result)
+}
+
+/// Execute fillOrderArgs for orders with extension data
+async fn execute_fill_order_args(
+    client: &Arc<SignerClient>,
+    router_contract: &AggregationRouterV6<SignerClient>,
+    request: FillRequest<'_>,
+) -> Result<FillResult> {
+    info!("🔄 Executing fillOrderArgs with extension...");
+    let FillRequest { order, taker, r, vs, contract_signature, amount, taker_traits, args, funding, allowance, native, mode } = request;
+    if contract_signature.is_some() && native.pays_native() {
+        return Err(eyre::eyre!("fillContractOrderArgs is not payable, a contract maker's order can't be paid in BNB"));
+    }
+
+    // Setup taker with required tokens and allowance
+
+    // Convert packed addresses back to Address type for balance checks
+    let mut taker_asset_bytes = [0u8; 32];
+    order.taker_asset.to_big_endian(&mut taker_asset_bytes);
+    let mut addr_bytes = [0u8; 20];
+    addr_bytes.copy_from_slice(&taker_asset_bytes[12..32]); // Take last 20 bytes
+    let taker_asset_addr = Address::from(addr_bytes);
+
+    // Check current balance
+    let current_balance = get_token_balance(client, taker_asset_addr, taker).await?;
+    info!("Current taker asset balance: {} wei", current_balance);
+
+    // We need at least 'amount' tokens to fill the order, unless a taker interaction pays for it
+    if native.pays_native() {
+        fund_native(client, taker, native.msg_value).await?;
+    } else if let Some(funding) = funding.filter(|_| current_balance < amount) {
+        info!("Insufficient balance. Need {} wei, have {} wei", amount, current_balance);
+        let required_amount: U256 = amount * 2; // Get 2x what we need for safety
+        info!("Setting {} tokens for taker", required_amount.as_u128() as f64 / 1e18);
+
+        match fund_account(client, taker_asset_addr, taker, required_amount, funding).await {
+            Ok(_) => info!("Successfully set token balance for taker"),
+            Err(e) => {
+                warn!("Failed to set token balance: {}", e);
+                return Err(e);
+            }
+        }
+
+        // Recheck balance
+        let new_balance = get_token_balance(client, taker_asset_addr, taker).await?;
+        info!("Recheck Current taker asset balance: {} wei", new_balance);
+    }
+
+    let allowance_amount: U256 = amount * 10; // Approve 10x for safety
+    let permit = match native.pays_native() {
+        true => None,
+        // Only the signer can sign permits, an impersonated taker approves
+        false if taker != client.address() => match allowance {
+            AllowanceStrategy::Approve => {
+                approve_token_from(client, taker_asset_addr, taker, router_contract.address(), allowance_amount).await?;
+                None
+            }
+            _ => return Err(eyre::eyre!("Impersonated taker {:?} can't sign a {:?} allowance, use approve", taker, allowance)),
+        },
+        false => match grant_allowance(client, client.signer(), &allowance, taker_asset_addr, router_contract.address(), allowance_amount).await {
+            Ok(permit) => {
+                info!("Successfully allowed 1inch router ({:?})", allowance);
+                permit
+            }
+            Err(e) => {
+                warn!("Failed to allow router: {}", e);
+                return Err(e);
+            }
+        },
+    };
+
+
+    let mut watches = fill_balance_watches(&order, taker);
+    let target = TakerTraits::decode(taker_traits, &args).ok().and_then(|t| t.target).unwrap_or(taker);
+    watches.extend(native.watches(&order, taker, target));
+    let call = match contract_signature {
+        Some(signature) => router_contract.fill_contract_order_args(order, signature, amount, taker_traits, args),
+        None => router_contract.fill_order_args(order, r, vs, amount, taker_traits, args).value(native.msg_value),
+    }.from(taker);
+    let result = match permit {
+        // The router applies the taker permit, then runs the fill as a delegatecall on itself
+        Some(permit) => {
+            let action = call.calldata().ok_or_else(|| eyre::eyre!("Failed to encode fillOrderArgs"))?;
+            let call = PermitAndCallFill::new(router_contract.address(), client.clone()).permit_and_call(permit, action).from(taker);
+            execute_call(client, call, mode, &watches).await
+        }
+        None => execute_call(client, call, mode, &watches).await,
+    };
+
+    match &result {
+        Ok(((actual_making_amount, actual_taking_amount, order_hash), receipt)) => {
+            info!("✅ fillOrderArgs successful!");
+            info!("  Actual Making Amount: {} wei ({:.6} tokens)", actual_making_amount, actual_making_amount.as_u128() as f64 / 1e18);
+            info!("  Actual Taking Amount: {} wei ({:.6} tokens)", actual_taking_amount, actual_taking_amount.as_u128() as f64 / 1e18);
+            info!("  Order Hash: 0x{}", hex::encode(order_hash));
+            log_native_deltas(receipt.as_ref(), taker);
+        }
+        Err(e) => error!("❌ fillOrderArgs simulation failed: {}", SimulationError::from_report(e)),
+    }
+
+    Ok(result)
+}
+
+
+
+/// Balances moved by a fill: taker and maker in both assets, receiver in the taker asset
+fn fill_balance_watches(order: &Order, taker: Address) -> Vec<BalanceWatch> {
+    let maker = unpack_address(order.maker);
+    let receiver = match unpack_address(order.receiver) {
+        receiver if receiver == Address::zero() => maker,
+        receiver => receiver,
+    };
+    let maker_asset = unpack_address(order.maker_asset);
+    let taker_asset = unpack_address(order.taker_asset);
+
+    vec![
+        BalanceWatch::new("taker taker asset", taker, taker_asset),
+        BalanceWatch::new("taker maker asset", taker, maker_asset),
+        BalanceWatch::new("maker maker asset", maker, maker_asset),
+        BalanceWatch::new("maker taker asset", maker, taker_asset),
+        BalanceWatch::new("receiver taker asset", receiver, taker_asset),
+    ]
+}
+
+/// `Address` as the uint256 word orders store it in
+pub(crate) fn pack_address(address: Address) -> U256 {
+    U256::from_big_endian(address.as_bytes())
+}
+
+/// Low 20 bytes of a packed `Address` word
+pub(crate) fn unpack_address(value: U256) -> Address {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    Address::from_slice(&bytes[12..])
+}
+
+async fn setup_oneinch_contract(client: &Arc<SignerClient>) -> Result<AggregationRouterV6<SignerClient>> {
+    let router_address = Address::from_str(AGGREGATION_ROUTER_V6)?;
+    let contract = AggregationRouterV6::new(router_address, client.clone());
+    Ok(contract)
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use ethers::{
+    providers::Middleware,
+    types::{Address, U256},
+};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::anvil_setup::SignerClient;
+use crate::bindings::aggregation_router_v6::Order;
+use crate::execution::BalanceWatch;
+use crate::funding::is_native_asset;
+use super::maker_traits::MakerTraits;
+use super::taker_traits::TakerTraits;
+use super::{WBNB, unpack_address};
+
+/// Where native BNB replaces WBNB in a fill
+///
+/// The router only accepts `msg.value` for a WBNB taker asset (refunding any excess), unwraps
+/// a WBNB taker asset for the maker receiver with the maker's UNWRAP_WETH, and unwraps a WBNB
+/// maker asset for the taker with the taker's UNWRAP_WETH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NativeFlow {
+    /// BNB the taker sends as `msg.value` for the taking amount, zero for a WBNB transfer
+    pub msg_value: U256,
+    /// Maker receiver gets BNB instead of WBNB
+    pub maker_unwrap: bool,
+    /// Taker (or the traits target) gets BNB instead of WBNB
+    pub taker_unwrap: bool,
+}
+
+impl NativeFlow {
+    /// Work out the native legs of a fill; flags the router would ignore only warn, but paying
+    /// native for another taker asset fails like the router would (InvalidMsgValue)
+    pub fn detect(order: &Order, taker_traits: U256, msg_value: U256) -> Result<Self> {
+        let wbnb: Address = WBNB.parse()?;
+        let maker_asset = unpack_address(order.maker_asset);
+        let taker_asset = unpack_address(order.taker_asset);
+
+        if is_native_asset(maker_asset) || is_native_asset(taker_asset) {
+            warn!("⚠️  Order uses a native asset placeholder; 1inch orders trade WBNB and unwrap it instead");
+        }
+        if !msg_value.is_zero() && taker_asset != wbnb {
+            return Err(eyre::eyre!("Can't pay {:?} with msg.value, the router only takes BNB for a WBNB taker asset", taker_asset));
+        }
+
+        let maker_wants_native = MakerTraits(order.maker_traits).unwrap_weth();
+        let taker_wants_native = TakerTraits::is_unwrap_weth(taker_traits);
+        if maker_wants_native && taker_asset != wbnb {
+            warn!("⚠️  Maker asks for UNWRAP_WETH but the taker asset isn't WBNB, the router ignores it");
+        }
+        if taker_wants_native && maker_asset != wbnb {
+            warn!("⚠️  Taker asks for UNWRAP_WETH but the maker asset isn't WBNB, the router ignores it");
+        }
+
+        let flow = Self {
+            msg_value,
+            maker_unwrap: maker_wants_native && taker_asset == wbnb,
+            taker_unwrap: taker_wants_native && maker_asset == wbnb,
+        };
+        if flow.is_native() {
+            info!("🪙 Native BNB legs: taker pays {} wei, maker receives native={}, taker receives native={}",
+                  flow.msg_value, flow.maker_unwrap, flow.taker_unwrap);
+        }
+        Ok(flow)
+    }
+
+    pub fn pays_native(&self) -> bool {
+        !self.msg_value.is_zero()
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.pays_native() || self.maker_unwrap || self.taker_unwrap
+    }
+
+    /// Native balances the fill moves, next to the ERC20 watches; `maker_asset_receiver` is
+    /// the traits target or the taker
+    pub fn watches(&self, order: &Order, taker: Address, maker_asset_receiver: Address) -> Vec<BalanceWatch> {
+        let maker = unpack_address(order.maker);
+        let receiver = match unpack_address(order.receiver) {
+            receiver if receiver == Address::zero() => maker,
+            receiver => receiver,
+        };
+
+        let mut watches = Vec::new();
+        if self.pays_native() || (self.taker_unwrap && maker_asset_receiver == taker) {
+            watches.push(BalanceWatch::new("taker native", taker, Address::zero()));
+        }
+        if self.taker_unwrap && maker_asset_receiver != taker {
+            watches.push(BalanceWatch::new("target native", maker_asset_receiver, Address::zero()));
+        }
+        if self.maker_unwrap {
+            watches.push(BalanceWatch::new("receiver native", receiver, Address::zero()));
+        }
+        watches
+    }
+}
+
+/// Make sure the taker holds `value` BNB plus gas money for a native fill
+pub async fn fund_native(client: &Arc<SignerClient>, account: Address, value: U256) -> Result<()> {
+    let required = value + U256::exp10(18);
+    let balance = client.get_balance(account, None).await?;
+    if balance < required {
+        info!("💰 Setting native balance of {:?} to {} wei for msg.value {}", account, required, value);
+        client.provider()
+            .request::<_, ()>("anvil_setBalance", (account, format!("0x{:x}", required)))
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,299 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::{
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, Signature, H256, U256},
+};
+use eyre::Result;
+use tracing::info;
+
+use crate::anvil_setup::SignerClient;
+use crate::bindings::aggregation_router_v6::Order;
+use crate::config::simple_config::{AllowanceStrategy, FundingStrategy, MevOneInchOrder, MevOrder};
+use crate::execution::ExecutionMode;
+use crate::funding::fund_account;
+use crate::simulation::SimulationOutcome;
+use super::extension::OrderExtension;
+use super::maker_traits::MakerTraits;
+use super::permit::grant_allowance;
+use super::signature::{order_hash, r_vs_from_signature};
+use super::{AGGREGATION_ROUTER_V6, fill_order_args, pack_address, unpack_address};
+
+/// Builds 1inch V6 limit orders for a maker we control on the fork
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    maker_asset: Address,
+    taker_asset: Address,
+    making_amount: U256,
+    taking_amount: U256,
+    receiver: Address,
+    maker_traits: MakerTraits,
+    extension: OrderExtension,
+    salt: Option<U256>,
+}
+
+impl OrderBuilder {
+    /// Order on the pair and amounts of a captured order
+    pub fn from_template(template: &MevOneInchOrder) -> Result<Self> {
+        Ok(Self::new(
+            Address::from_str(&template.order.maker_asset)?,
+            Address::from_str(&template.order.taker_asset)?,
+            MevOneInchOrder::parse_amount(&template.order.making_amount)?,
+            MevOneInchOrder::parse_amount(&template.order.taking_amount)?,
+        ))
+    }
+
+    /// Order selling `making_amount` of `maker_asset` for `taking_amount` of `taker_asset`;
+    /// defaults to partial and multiple fills allowed, no expiry, proceeds to the maker
+    pub fn new(maker_asset: Address, taker_asset: Address, making_amount: U256, taking_amount: U256) -> Self {
+        Self {
+            maker_asset,
+            taker_asset,
+            making_amount,
+            taking_amount,
+            receiver: Address::zero(),
+            maker_traits: MakerTraits::default().with_multiple_fills(true),
+            extension: OrderExtension::default(),
+            salt: None,
+        }
+    }
+
+    pub fn receiver(mut self, receiver: Address) -> Self {
+        self.receiver = receiver;
+        self
+    }
+
+    /// Extension-related flags are derived from the extension in `build`
+    pub fn maker_traits(mut self, maker_traits: MakerTraits) -> Self {
+        self.maker_traits = maker_traits;
+        self
+    }
+
+    pub fn extension(mut self, extension: OrderExtension) -> Self {
+        self.extension = extension;
+        self
+    }
+
+    /// `[token][permit]` the router applies before the first fill, see `permit::grant_allowance`
+    pub fn maker_permit(mut self, permit: Bytes) -> Self {
+        self.extension.maker_permit = permit;
+        self
+    }
+
+    /// Upper 96 bits are kept; the low 160 bits carry the extension hash when there is one
+    pub fn salt(mut self, salt: U256) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+
+    /// The order struct for `maker`
+    pub fn build(&self, maker: Address) -> Order {
+        let traits = self.maker_traits
+            .with_extension(!self.extension.is_empty())
+            .with_pre_interaction_call(!self.extension.pre_interaction_data.is_empty())
+            .with_post_interaction_call(!self.extension.post_interaction_data.is_empty());
+
+        let low_160 = (U256::one() << 160) - 1;
+        let salt = self.salt.unwrap_or_else(|| U256::from(chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64) << 160);
+        let salt = if self.extension.is_empty() {
+            salt
+        } else {
+            (salt & !low_160) | self.extension.hash_low_160()
+        };
+
+        Order {
+            salt,
+            maker: pack_address(maker),
+            receiver: pack_address(self.receiver),
+            maker_asset: pack_address(self.maker_asset),
+            taker_asset: pack_address(self.taker_asset),
+            making_amount: self.making_amount,
+            taking_amount: self.taking_amount,
+            maker_traits: traits.raw(),
+        }
+    }
+
+    /// Build and EIP-712 sign the order with `wallet` as maker
+    pub fn sign(&self, wallet: &LocalWallet, router: Address) -> Result<SignedOrder> {
+        let order = self.build(wallet.address());
+        let hash = order_hash(&order, wallet.chain_id(), router);
+        let signature = wallet.sign_hash(hash)?;
+        info!("✍️  Signed order {:?} as maker {:?}", hash, wallet.address());
+
+        Ok(SignedOrder { order, extension: self.extension.clone(), hash, signature })
+    }
+}
+
+/// An order with its extension, EIP-712 hash and maker signature
+#[derive(Debug, Clone)]
+pub struct SignedOrder {
+    pub order: Order,
+    pub extension: OrderExtension,
+    pub hash: H256,
+    pub signature: Signature,
+}
+
+impl SignedOrder {
+    pub fn r(&self) -> [u8; 32] {
+        r_vs_from_signature(&self.signature).0
+    }
+
+    /// EIP-2098 compact `vs`: `s` with `v - 27` in the top bit
+    pub fn vs(&self) -> [u8; 32] {
+        r_vs_from_signature(&self.signature).1
+    }
+
+    /// Captured-order form so the fill path treats it like MEV data; `amount_in` is the
+    /// taking amount to fill and `amount_out` the proportional making amount
+    pub fn to_mev_order(&self, amount_in: U256, block_number: Option<u64>) -> MevOneInchOrder {
+        let amount_out = if self.order.taking_amount.is_zero() {
+            U256::zero()
+        } else {
+            amount_in * self.order.making_amount / self.order.taking_amount
+        };
+
+        MevOneInchOrder {
+            amount_in: format!("0x{:x}", amount_in),
+            amount_out: format!("0x{:x}", amount_out),
+            order: MevOrder {
+                order_hash: format!("{:?}", self.hash),
+                salt: format!("0x{:x}", self.order.salt),
+                maker: format!("{:?}", unpack_address(self.order.maker)),
+                receiver: format!("{:?}", unpack_address(self.order.receiver)),
+                maker_asset: format!("{:?}", unpack_address(self.order.maker_asset)),
+                taker_asset: format!("{:?}", unpack_address(self.order.taker_asset)),
+                making_amount: format!("0x{:x}", self.order.making_amount),
+                remaining_making_amount: format!("0x{:x}", self.order.making_amount),
+                taking_amount: format!("0x{:x}", self.order.taking_amount),
+                maker_traits: format!("0x{:x}", self.order.maker_traits),
+                extension: format!("0x{}", hex::encode(self.extension.encode())),
+                signature: format!("0x{}", hex::encode(self.signature.to_vec())),
+            },
+            block_number,
+        }
+    }
+}
+
+/// Give the maker the making amount and approve the router for it; call before `sign`
+pub async fn prepare_maker(
+    client: &Arc<SignerClient>,
+    maker: &LocalWallet,
+    builder: OrderBuilder,
+    router: Address,
+    funding: &FundingStrategy,
+) -> Result<OrderBuilder> {
+    prepare_maker_with(client, maker, builder, router, funding, &AllowanceStrategy::Approve).await
+}
+
+/// Give the maker the making amount and let the router pull it per `allowance`; permits are
+/// signed into the builder's MakerPermit (and Permit2 sets USE_PERMIT2), so call this before `sign`
+pub async fn prepare_maker_with(
+    client: &Arc<SignerClient>,
+    maker: &LocalWallet,
+    builder: OrderBuilder,
+    router: Address,
+    funding: &FundingStrategy,
+    allowance: &AllowanceStrategy,
+) -> Result<OrderBuilder> {
+    info!("💰 Funding maker {:?} with {} of {:?}", maker.address(), builder.making_amount, builder.maker_asset);
+    fund_account(client, builder.maker_asset, maker.address(), builder.making_amount, funding).await?;
+    // Gas for the approval
+    client.provider()
+        .request::<_, ()>("anvil_setBalance", (maker.address(), format!("0x{:x}", U256::exp10(18))))
+        .await?;
+
+    let permit = grant_allowance(client, maker, allowance, builder.maker_asset, router, builder.making_amount).await?;
+    let builder = match permit {
+        Some(permit) => builder.maker_permit(permit),
+        None => builder,
+    };
+    Ok(match allowance {
+        AllowanceStrategy::Permit2 => {
+            let traits = builder.maker_traits.with_permit2(true);
+            builder.maker_traits(traits)
+        }
+        _ => builder,
+    })
+}
+
+/// Have `maker` sign an order on `template`'s pair and amounts (no extension), fund and
+/// approve the maker, then fill all of it from the taker through `fill_order_args`
+pub async fn fill_self_order(
+    client: &Arc<SignerClient>,
+    maker: &LocalWallet,
+    template: &MevOneInchOrder,
+    maker_funding: &FundingStrategy,
+    taker_funding: &FundingStrategy,
+    mode: ExecutionMode,
+) -> Result<SimulationOutcome> {
+    let router = Address::from_str(AGGREGATION_ROUTER_V6)?;
+    let builder = OrderBuilder::from_template(template)?;
+    let signed = prepare_maker(client, maker, builder, router, maker_funding).await?.sign(maker, router)?;
+
+    let mev_order = signed.to_mev_order(signed.order.taking_amount, template.block_number);
+    let mut order_config = mev_order.to_standard_order("0x0")?;
+    order_config.funding = Some(taker_funding.clone());
+    fill_order_args(&order_config, &mev_order.order.extension, client, mode).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::simple_config::MevConfig;
+    use crate::one_inch::signature::recover_signer;
+    use crate::one_inch::{parse_order, parse_signature};
+
+    fn maker() -> LocalWallet {
+        // Anvil dev account 1
+        "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d".parse::<LocalWallet>().unwrap().with_chain_id(56u64)
+    }
+
+    #[test]
+    fn builds_from_a_captured_template() {
+        let config: MevConfig = serde_json::from_str(include_str!("../../mev_data_updated.json")).unwrap();
+        let template = &config.one_inch_orders[0];
+        let builder = OrderBuilder::from_template(template).unwrap();
+        assert_eq!(builder.maker_asset, Address::from_str(&template.order.maker_asset).unwrap());
+        assert_eq!(builder.taker_asset, Address::from_str(&template.order.taker_asset).unwrap());
+        assert_eq!(builder.making_amount, U256::from_dec_str("1500000000000000000000000").unwrap());
+        assert_eq!(builder.taking_amount, U256::from_dec_str("56250000000000000000000").unwrap());
+        assert_eq!(MevOneInchOrder::parse_amount("0x1e5b8fa8fe2ac000").unwrap(), U256::from(2_187_500_000_000_000_000u64));
+    }
+
+    #[test]
+    fn signed_order_survives_the_captured_order_path() {
+        let router = Address::from_str(AGGREGATION_ROUTER_V6).unwrap();
+        let maker_asset = Address::from_low_u64_be(0xaaaa);
+        let taker_asset = Address::from_low_u64_be(0xbbbb);
+        let signed = OrderBuilder::new(maker_asset, taker_asset, U256::exp10(18), U256::from(3) * U256::exp10(18))
+            .salt(U256::from(42) << 160)
+            .sign(&maker(), router)
+            .unwrap();
+
+        let mev_order = signed.to_mev_order(U256::exp10(18), None);
+        assert_eq!(mev_order.amount_out, format!("0x{:x}", U256::exp10(18) / 3));
+        let order_config = mev_order.to_standard_order("0x0").unwrap();
+        let order = parse_order(&order_config).unwrap();
+        let (r, vs) = parse_signature(&order_config).unwrap();
+
+        assert_eq!(order.salt, signed.order.salt);
+        assert_eq!(order.maker_traits, signed.order.maker_traits);
+        assert_eq!(unpack_address(order.maker), maker().address());
+        assert_eq!((r, vs), (signed.r(), signed.vs()));
+        assert_eq!(order_hash(&order, 56, router), signed.hash);
+        assert_eq!(recover_signer(signed.hash, r, vs).unwrap(), maker().address());
+    }
+
+    #[test]
+    fn extension_sets_flags_and_salt() {
+        let mut extension = OrderExtension::default();
+        extension.post_interaction_data = Bytes::from(vec![0x11; 24]);
+        let order = OrderBuilder::new(Address::zero(), Address::zero(), U256::one(), U256::one())
+            .extension(extension.clone())
+            .build(maker().address());
+        let traits = MakerTraits(order.maker_traits);
+        assert!(traits.has_extension() && traits.need_post_interaction_call());
+        assert!(extension.matches_salt(order.salt));
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::types::{Address, H256, U256};
+use eyre::Result;
+use tracing::info;
+
+use crate::anvil_setup::SignerClient;
+use crate::bindings::aggregation_router_v6::{AggregationRouterV6, Order};
+use crate::revert::SimulationError;
+use super::maker_traits::MakerTraits;
+use super::{AGGREGATION_ROUTER_V6, unpack_address};
+
+/// Fill state of an order according to the router's invalidators on the fork
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    /// Making amount that can still be filled
+    Fillable(U256),
+    /// Nothing left to fill; the invalidators can't tell a completed fill from a
+    /// `cancelOrder`, so both read as filled
+    Filled,
+    /// The maker advanced the epoch of the order's series
+    Cancelled,
+    Expired,
+}
+
+impl OrderStatus {
+    pub fn remaining(&self) -> Option<U256> {
+        match self {
+            OrderStatus::Fillable(remaining) => Some(*remaining),
+            _ => None,
+        }
+    }
+
+    /// The revert the router would raise for a fill in this state
+    pub fn rejection(&self, traits: MakerTraits) -> Option<SimulationError> {
+        match self {
+            OrderStatus::Fillable(_) => None,
+            OrderStatus::Filled if traits.use_bit_invalidator() => Some(SimulationError::BitInvalidatedOrder),
+            OrderStatus::Filled => Some(SimulationError::InvalidatedOrder),
+            OrderStatus::Cancelled => Some(SimulationError::WrongSeriesNonce),
+            OrderStatus::Expired => Some(SimulationError::OrderExpired),
+        }
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderStatus::Fillable(remaining) => write!(f, "fillable ({} remaining)", remaining),
+            OrderStatus::Filled => write!(f, "filled"),
+            OrderStatus::Cancelled => write!(f, "cancelled"),
+            OrderStatus::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+/// Look the order up in the router's invalidators, choosing the bit or remaining-amount
+/// invalidator the same way the router does
+pub async fn order_status(client: &Arc<SignerClient>, order: &Order, order_hash: H256, timestamp: u64) -> Result<OrderStatus> {
+    let traits = MakerTraits(order.maker_traits);
+    if traits.is_expired(timestamp) {
+        return Ok(OrderStatus::Expired);
+    }
+
+    let router = AggregationRouterV6::new(Address::from_str(AGGREGATION_ROUTER_V6)?, client.clone());
+    let maker = unpack_address(order.maker);
+
+    if traits.need_check_epoch_manager() {
+        let current = router
+            .epoch_equals(maker, traits.series().into(), traits.nonce_or_epoch().into())
+            .call()
+            .await?;
+        if !current {
+            return Ok(OrderStatus::Cancelled);
+        }
+    }
+
+    let status = if traits.use_bit_invalidator() {
+        let nonce = traits.nonce_or_epoch();
+        let (argument, bit) = bit_invalidator_query(nonce);
+        let slot = router.bit_invalidator_for_order(maker, argument).call().await?;
+        info!("🔎 Bit invalidator word {} for {:?}: 0x{:x}", nonce >> 8, maker, slot);
+        if slot.bit(bit) {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::Fillable(order.making_amount)
+        }
+    } else {
+        // RemainingInvalidatorLib stores `~remaining`, with 0 meaning never filled.
+        // `remainingInvalidatorForOrder` reverts for new orders, so read the raw slot
+        let raw = router.raw_remaining_invalidator_for_order(maker, order_hash.into()).call().await?;
+        info!("🔎 Remaining invalidator for {:?}: 0x{:x}", order_hash, raw);
+        if raw.is_zero() {
+            OrderStatus::Fillable(order.making_amount)
+        } else if raw == U256::MAX {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::Fillable(!raw)
+        }
+    };
+
+    Ok(status)
+}
+
+/// `bitInvalidatorForOrder` argument and the bit to test in the word it returns; the router
+/// does the `nonce >> 8` itself (BitInvalidatorLib.checkSlot), so it gets the whole nonce
+fn bit_invalidator_query(nonce: u64) -> (U256, usize) {
+    (U256::from(nonce), (nonce & 0xff) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BitInvalidatorLib.checkSlot: word `nonce >> 8` of the maker's bitmap
+    fn router_bitmap_word(bitmap: &[U256], argument: U256) -> U256 {
+        bitmap.get((argument >> 8).as_usize()).copied().unwrap_or_default()
+    }
+
+    #[test]
+    fn nonce_above_255_reads_its_own_word() {
+        // Nonce 300 lives in word 1, bit 44; word 0 has an unrelated bit 44 cleared
+        let nonce = 300;
+        let bitmap = [U256::zero(), U256::one() << 44];
+        let (argument, bit) = bit_invalidator_query(nonce);
+        assert_eq!(argument, U256::from(300));
+        assert_eq!(bit, 44);
+        assert!(router_bitmap_word(&bitmap, argument).bit(bit));
+    }
+
+    #[test]
+    fn low_nonce_and_word_boundary() {
+        assert_eq!(bit_invalidator_query(7), (U256::from(7), 7));
+        assert_eq!(bit_invalidator_query(0x1ff), (U256::from(0x1ff), 255));
+        assert_eq!(bit_invalidator_query(0x200), (U256::from(0x200), 0));
+    }
+}
@@ -0,0 +1,277 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::{
+    abi::{encode, Token},
+    signers::{LocalWallet, Signer},
+    types::{Address, BlockNumber, Bytes, Signature, H256, U256},
+    utils::keccak256,
+};
+use eyre::Result;
+use tracing::info;
+
+use crate::anvil_setup::{SignerClient, approve_token, signer_client};
+use crate::bindings::erc20_permit::IERC20Permit;
+use crate::bindings::permit2::Permit2;
+use crate::config::simple_config::AllowanceStrategy;
+use super::signature::r_vs_from_signature;
+use super::{PERMIT2, block_timestamp};
+
+const PERMIT_TYPE: &str = "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)";
+const PERMIT_DETAILS_TYPE: &str = "PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)";
+const PERMIT_SINGLE_TYPE: &str = "PermitSingle(PermitDetails details,address spender,uint256 sigDeadline)PermitDetails(address token,uint160 amount,uint48 expiration,uint48 nonce)";
+const TOKEN_PERMISSIONS_TYPE: &str = "TokenPermissions(address token,uint256 amount)";
+const PERMIT_TRANSFER_FROM_TYPE: &str = "PermitTransferFrom(TokenPermissions permitted,address spender,uint256 nonce,uint256 deadline)TokenPermissions(address token,uint256 amount)";
+
+/// Seconds a permit signed by `grant_allowance` stays valid
+const PERMIT_LIFETIME: u64 = 3600;
+
+/// EIP-2612 permit, as the 224-byte `permit(owner, spender, value, deadline, v, r, s)` arguments
+/// the router's `tryPermit` forwards to the token
+pub async fn sign_erc2612_permit(
+    client: &Arc<SignerClient>,
+    owner: &LocalWallet,
+    token: Address,
+    spender: Address,
+    value: U256,
+    deadline: u64,
+) -> Result<Bytes> {
+    let contract = IERC20Permit::new(token, client.clone());
+    let domain_separator = contract.domain_separator().call().await
+        .map_err(|e| eyre::eyre!("{:?} does not support EIP-2612 (no DOMAIN_SEPARATOR): {}", token, e))?;
+    let nonce = contract.nonces(owner.address()).call().await?;
+
+    let struct_hash = keccak256(encode(&[
+        Token::FixedBytes(keccak256(PERMIT_TYPE).to_vec()),
+        Token::Address(owner.address()),
+        Token::Address(spender),
+        Token::Uint(value),
+        Token::Uint(nonce),
+        Token::Uint(U256::from(deadline)),
+    ]));
+    let signature = owner.sign_hash(eip712_digest(domain_separator, struct_hash))?;
+    info!("✍️  Signed EIP-2612 permit: {:?} lets {:?} spend {} of {:?} (nonce {})", owner.address(), spender, value, token, nonce);
+
+    Ok(encode(&[
+        Token::Address(owner.address()),
+        Token::Address(spender),
+        Token::Uint(value),
+        Token::Uint(U256::from(deadline)),
+        Token::Uint(U256::from(signature.v)),
+        Token::FixedBytes(word(signature.r).to_vec()),
+        Token::FixedBytes(word(signature.s).to_vec()),
+    ]).into())
+}
+
+/// Permit2 `PermitSingle`, as the 352-byte `permit(owner, permitSingle, signature)` arguments
+/// with an EIP-2098 compact signature
+pub async fn sign_permit2_single(
+    client: &Arc<SignerClient>,
+    owner: &LocalWallet,
+    token: Address,
+    spender: Address,
+    amount: U256,
+    expiration: u64,
+    sig_deadline: u64,
+) -> Result<Bytes> {
+    if amount > (U256::one() << 160) - 1 {
+        return Err(eyre::eyre!("Permit2 amount {} does not fit uint160", amount));
+    }
+    let permit2 = Permit2::new(Address::from_str(PERMIT2)?, client.clone());
+    let domain_separator = permit2.domain_separator().call().await?;
+    let (_, _, nonce) = permit2.allowance(owner.address(), token, spender).call().await?;
+
+    let details = [
+        Token::Address(token),
+        Token::Uint(amount),
+        Token::Uint(U256::from(expiration)),
+        Token::Uint(U256::from(nonce)),
+    ];
+    let mut details_hash = vec![Token::FixedBytes(keccak256(PERMIT_DETAILS_TYPE).to_vec())];
+    details_hash.extend_from_slice(&details);
+    let struct_hash = keccak256(encode(&[
+        Token::FixedBytes(keccak256(PERMIT_SINGLE_TYPE).to_vec()),
+        Token::FixedBytes(keccak256(encode(&details_hash)).to_vec()),
+        Token::Address(spender),
+        Token::Uint(U256::from(sig_deadline)),
+    ]));
+    let signature = owner.sign_hash(eip712_digest(domain_separator, struct_hash))?;
+    info!("✍️  Signed Permit2 PermitSingle: {:?} lets {:?} spend {} of {:?} until {} (nonce {})", owner.address(), spender, amount, token, expiration, nonce);
+
+    Ok(encode(&[
+        Token::Address(owner.address()),
+        Token::Tuple(vec![Token::Tuple(details.to_vec()), Token::Address(spender), Token::Uint(U256::from(sig_deadline))]),
+        Token::Bytes(compact_signature(&signature).to_vec()),
+    ]).into())
+}
+
+/// Permit2 `PermitTransferFrom`, as the `(permit, owner, signature)` arguments of
+/// `permitTransferFrom` around the caller's transfer details
+///
+/// SignatureTransfer permits are spent by `spender` in one call with an unordered nonce.
+/// The router only transfers through AllowanceTransfer, so fills use `sign_permit2_single`;
+/// this is for executors that pull funds themselves.
+pub async fn sign_permit2_transfer_from(
+    client: &Arc<SignerClient>,
+    owner: &LocalWallet,
+    token: Address,
+    spender: Address,
+    amount: U256,
+    deadline: u64,
+) -> Result<Bytes> {
+    let permit2 = Permit2::new(Address::from_str(PERMIT2)?, client.clone());
+    let domain_separator = permit2.domain_separator().call().await?;
+    let mut word_pos = U256::zero();
+    let nonce = loop {
+        let bitmap = permit2.nonce_bitmap(owner.address(), word_pos).call().await?;
+        if let Some(nonce) = unused_nonce(word_pos, bitmap) {
+            break nonce;
+        }
+        word_pos += U256::one();
+    };
+
+    let struct_hash = permit_transfer_from_hash(token, amount, spender, nonce, deadline);
+    let signature = owner.sign_hash(eip712_digest(domain_separator, struct_hash))?;
+    info!("✍️  Signed Permit2 PermitTransferFrom: {:?} lets {:?} take {} of {:?} (nonce {})", owner.address(), spender, amount, token, nonce);
+
+    Ok(encode(&[
+        Token::Tuple(vec![
+            Token::Tuple(vec![Token::Address(token), Token::Uint(amount)]),
+            Token::Uint(nonce),
+            Token::Uint(U256::from(deadline)),
+        ]),
+        Token::Address(owner.address()),
+        Token::Bytes(signature.to_vec()),
+    ]).into())
+}
+
+fn permit_transfer_from_hash(token: Address, amount: U256, spender: Address, nonce: U256, deadline: u64) -> [u8; 32] {
+    let permitted = keccak256(encode(&[
+        Token::FixedBytes(keccak256(TOKEN_PERMISSIONS_TYPE).to_vec()),
+        Token::Address(token),
+        Token::Uint(amount),
+    ]));
+    keccak256(encode(&[
+        Token::FixedBytes(keccak256(PERMIT_TRANSFER_FROM_TYPE).to_vec()),
+        Token::FixedBytes(permitted.to_vec()),
+        Token::Address(spender),
+        Token::Uint(nonce),
+        Token::Uint(U256::from(deadline)),
+    ]))
+}
+
+/// Lowest unused nonce in word `word_pos` of Permit2's `nonceBitmap`: word position in
+/// the high 248 bits, bit position in the low 8
+fn unused_nonce(word_pos: U256, bitmap: U256) -> Option<U256> {
+    (0..256).find(|bit| !bitmap.bit(*bit)).map(|bit| (word_pos << 8) | U256::from(bit))
+}
+
+/// Let `spender` pull `amount` of `token` from `owner` per `strategy`
+///
+/// `Approve` sends the approval. The permit strategies return `[token][permit]`, which the
+/// router applies itself: as the `permit` of `permitAndCall` for the taker, or as the
+/// MakerPermit extension field (first fill only) for the maker.
+pub async fn grant_allowance(
+    client: &Arc<SignerClient>,
+    owner: &LocalWallet,
+    strategy: &AllowanceStrategy,
+    token: Address,
+    spender: Address,
+    amount: U256,
+) -> Result<Option<Bytes>> {
+    let deadline = block_timestamp(client, BlockNumber::Latest).await? + PERMIT_LIFETIME;
+    let permit = match strategy {
+        AllowanceStrategy::Approve => {
+            approve_token(&signer_client(client, owner.clone()), token, spender, amount).await?;
+            return Ok(None);
+        }
+        AllowanceStrategy::Permit => sign_erc2612_permit(client, owner, token, spender, amount, deadline).await?,
+        AllowanceStrategy::Permit2 => {
+            // Permit2 still needs a plain approval, once per token
+            approve_token(&signer_client(client, owner.clone()), token, Address::from_str(PERMIT2)?, U256::MAX).await?;
+            let amount = amount.min((U256::one() << 160) - 1);
+            sign_permit2_single(client, owner, token, spender, amount, deadline, deadline).await?
+        }
+    };
+
+    let mut data = token.as_bytes().to_vec();
+    data.extend_from_slice(&permit);
+    Ok(Some(data.into()))
+}
+
+fn eip712_digest(domain_separator: [u8; 32], struct_hash: [u8; 32]) -> H256 {
+    let mut data = Vec::with_capacity(66);
+    data.extend_from_slice(&[0x19, 0x01]);
+    data.extend_from_slice(&domain_separator);
+    data.extend_from_slice(&struct_hash);
+    H256::from(keccak256(data))
+}
+
+fn word(value: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+}
+
+/// EIP-2098 `r || vs`
+fn compact_signature(signature: &Signature) -> [u8; 64] {
+    let (r, vs) = r_vs_from_signature(signature);
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(&r);
+    bytes[32..].copy_from_slice(&vs);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn h256(hex: &str) -> [u8; 32] {
+        H256::from_str(hex).unwrap().0
+    }
+
+    #[test]
+    fn permit_typehashes() {
+        // PERMIT_TYPEHASH of OpenZeppelin ERC20Permit, and Permit2's PermitHash library
+        assert_eq!(keccak256(PERMIT_TYPE), h256("0x6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9"));
+        assert_eq!(keccak256(PERMIT_DETAILS_TYPE), h256("0x65626cad6cb96493bf6f5ebea28756c966f023ab9e8a83a7101849d5573b3678"));
+        assert_eq!(keccak256(PERMIT_SINGLE_TYPE), h256("0xf3841cd1ff0085026a6327b620b67997ce40f282c88a8e905a7a5626e310f3d0"));
+        assert_eq!(keccak256(TOKEN_PERMISSIONS_TYPE), h256("0x618358ac3db8dc274f0cd8829da7e234bd48cd73c4a740aede1adec9846d06a1"));
+        assert_eq!(keccak256(PERMIT_TRANSFER_FROM_TYPE), h256("0x939c21a48a8dbe3a9a2404a1d46691e4d39f6583d6ec6b35714604c986d80106"));
+    }
+
+    #[test]
+    fn permit_transfer_from_struct_hash() {
+        let hash = permit_transfer_from_hash(Address::repeat_byte(0x11), U256::exp10(18), Address::repeat_byte(0x22), U256::from(257), 1_700_000_000);
+        assert_eq!(hash, h256("0x4ba80892a4f1f9a523fd1b06635b998dbd56d8f9e916ff74d4295c4c8ae1f1ec"));
+    }
+
+    #[test]
+    fn unused_nonces() {
+        assert_eq!(unused_nonce(U256::zero(), U256::zero()), Some(U256::zero()));
+        assert_eq!(unused_nonce(U256::zero(), U256::from(0b0111)), Some(U256::from(3)));
+        assert_eq!(unused_nonce(U256::from(2), U256::one()), Some(U256::from(513)));
+        assert_eq!(unused_nonce(U256::one(), U256::MAX), None);
+    }
+
+    #[test]
+    fn eip712_mail_vector() {
+        // The `Mail` example of the EIP-712 specification, signed with keccak256("cow")
+        let domain_separator = h256("0xf2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f");
+        let struct_hash = h256("0xc52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e");
+        let digest = eip712_digest(domain_separator, struct_hash);
+        assert_eq!(digest, H256::from_str("0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2").unwrap());
+
+        let wallet = LocalWallet::from_bytes(&keccak256("cow")).unwrap();
+        assert_eq!(wallet.address(), Address::from_str("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826").unwrap());
+        let signature = wallet.sign_hash(digest).unwrap();
+        assert_eq!(signature.v, 28);
+        assert_eq!(word(signature.r), h256("0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d"));
+        assert_eq!(word(signature.s), h256("0x07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562"));
+
+        // v = 28 goes into the top bit of vs
+        let compact = compact_signature(&signature);
+        assert_eq!(compact[..32], word(signature.r));
+        assert_eq!(compact[32], 0x80 | 0x07);
+        assert_eq!(compact[33..], word(signature.s)[1..]);
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::{
+    abi::{AbiParser, Function, Token},
+    types::{Address, Bytes, U256},
+};
+use eyre::Result;
+use tracing::info;
+
+use crate::anvil_setup::SignerClient;
+use crate::bindings::aggregation_router_v6::{AGGREGATIONROUTERV6_ABI, AggregationRouterV6};
+use crate::revert::format_token;
+use super::AGGREGATION_ROUTER_V6;
+
+/// Calls commonly wrapped in `arbitraryStaticCall`, for naming leaves
+const KNOWN_STATIC_CALLS: [&str; 6] = [
+    "function timestampBelow(uint256 time) returns (bool)",
+    "function timestampBelowAndNonceEquals(uint256 timeNonceAccount) returns (bool)",
+    "function nonceEquals(address maker, uint256 makerNonce) returns (bool)",
+    "function balanceOf(address account) returns (uint256)",
+    "function allowance(address owner, address spender) returns (uint256)",
+    "function getReserves() returns (uint112, uint112, uint32)",
+];
+
+/// A 1inch V6 order predicate: calldata the router `staticcall`s on itself, where
+/// `and`/`or`/`not`/`eq`/`lt`/`gt` nest further predicates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+    Eq(U256, Box<Predicate>),
+    Lt(U256, Box<Predicate>),
+    Gt(U256, Box<Predicate>),
+    /// `arbitraryStaticCall(target, data)`: the uint256 returned by another contract
+    StaticCall { target: Address, call: PredicateCall },
+    /// Any other call on the router itself, e.g. `epochEquals`
+    RouterCall(PredicateCall),
+}
+
+/// A leaf call, named when its selector is known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PredicateCall {
+    pub name: Option<String>,
+    pub args: Vec<String>,
+    pub data: Bytes,
+}
+
+impl Predicate {
+    /// Decode the `Predicate` field of an order extension
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let selector = data.get(..4).ok_or_else(|| eyre::eyre!("Predicate is {} bytes, shorter than a selector", data.len()))?;
+        let Some(function) = AGGREGATIONROUTERV6_ABI.functions().find(|f| f.short_signature() == selector) else {
+            return Ok(Predicate::RouterCall(PredicateCall::unknown(data)));
+        };
+        let tokens = function.decode_input(&data[4..])?;
+
+        let predicate = match (function.name.as_str(), tokens.as_slice()) {
+            ("and", [Token::Uint(offsets), Token::Bytes(inner)]) => Predicate::And(split_offsets(*offsets, inner)?),
+            ("or", [Token::Uint(offsets), Token::Bytes(inner)]) => Predicate::Or(split_offsets(*offsets, inner)?),
+            ("not", [Token::Bytes(inner)]) => Predicate::Not(Box::new(Self::decode(inner)?)),
+            ("eq", [Token::Uint(value), Token::Bytes(inner)]) => Predicate::Eq(*value, Box::new(Self::decode(inner)?)),
+            ("lt", [Token::Uint(value), Token::Bytes(inner)]) => Predicate::Lt(*value, Box::new(Self::decode(inner)?)),
+            ("gt", [Token::Uint(value), Token::Bytes(inner)]) => Predicate::Gt(*value, Box::new(Self::decode(inner)?)),
+            ("arbitraryStaticCall", [Token::Address(target), Token::Bytes(inner)]) => {
+                Predicate::StaticCall { target: *target, call: PredicateCall::known(inner) }
+            }
+            _ => Predicate::RouterCall(PredicateCall::from_function(function, &tokens, data)),
+        };
+        Ok(predicate)
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |items: &[Predicate]| items.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        match self {
+            Predicate::And(items) => write!(f, "and({})", join(items)),
+            Predicate::Or(items) => write!(f, "or({})", join(items)),
+            Predicate::Not(inner) => write!(f, "not({})", inner),
+            Predicate::Eq(value, inner) => write!(f, "{} == {}", inner, value),
+            Predicate::Lt(value, inner) => write!(f, "{} < {}", inner, value),
+            Predicate::Gt(value, inner) => write!(f, "{} > {}", inner, value),
+            Predicate::StaticCall { target, call } => write!(f, "{:?}.{}", target, call),
+            Predicate::RouterCall(call) => write!(f, "router.{}", call),
+        }
+    }
+}
+
+impl PredicateCall {
+    fn unknown(data: &[u8]) -> Self {
+        Self { name: None, args: Vec::new(), data: Bytes::from(data.to_vec()) }
+    }
+
+    fn from_function(function: &Function, tokens: &[Token], data: &[u8]) -> Self {
+        Self {
+            name: Some(function.name.clone()),
+            args: tokens.iter().map(format_token).collect(),
+            data: Bytes::from(data.to_vec()),
+        }
+    }
+
+    /// Name the call if it is one of `KNOWN_STATIC_CALLS`
+    fn known(data: &[u8]) -> Self {
+        let Some(selector) = data.get(..4) else {
+            return Self::unknown(data);
+        };
+        let mut parser = AbiParser::default();
+        KNOWN_STATIC_CALLS.iter()
+            .filter_map(|signature| parser.parse_function(signature).ok())
+            .find(|function| function.short_signature() == selector)
+            .and_then(|function| {
+                let tokens = function.decode_input(&data[4..]).ok()?;
+                Some(Self::from_function(&function, &tokens, data))
+            })
+            .unwrap_or_else(|| Self::unknown(data))
+    }
+}
+
+impl fmt::Display for PredicateCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}({})", name, self.args.join(", ")),
+            None => write!(f, "call({})", self.data),
+        }
+    }
+}
+
+/// `and`/`or` operands: `offsets` packs the uint32 end offset of each one into `data`
+fn split_offsets(offsets: U256, data: &[u8]) -> Result<Vec<Predicate>> {
+    let mut items = Vec::new();
+    let mut previous = 0usize;
+    for i in 0..8 {
+        let current = ((offsets >> (32 * i)) & U256::from(u32::MAX)).as_usize();
+        if current == 0 {
+            break;
+        }
+        if current < previous || current > data.len() {
+            return Err(eyre::eyre!("Predicate operand {} spans {}..{} of {} bytes", i, previous, current, data.len()));
+        }
+        items.push(Predicate::decode(&data[previous..current])?);
+        previous = current;
+    }
+    Ok(items)
+}
+
+/// Evaluate the predicate on the fork with the router's own `checkPredicate`
+pub async fn check_predicate(client: &Arc<SignerClient>, predicate: &[u8]) -> Result<bool> {
+    let router = AggregationRouterV6::new(Address::from_str(AGGREGATION_ROUTER_V6)?, client.clone());
+    let result = router.check_predicate(Bytes::from(predicate.to_vec())).call().await?;
+    info!("🔮 Predicate evaluates to {} on the fork", result);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router_call(name: &str, args: &[Token]) -> Vec<u8> {
+        AGGREGATIONROUTERV6_ABI.function(name).unwrap().encode_input(args).unwrap()
+    }
+
+    fn epoch_equals(epoch: u64) -> Vec<u8> {
+        router_call("epochEquals", &[Token::Address(Address::repeat_byte(0xaa)), Token::Uint(U256::zero()), Token::Uint(epoch.into())])
+    }
+
+    fn balance_of(account: Address) -> Vec<u8> {
+        let function = AbiParser::default().parse_function(KNOWN_STATIC_CALLS[3]).unwrap();
+        function.encode_input(&[Token::Address(account)]).unwrap()
+    }
+
+    /// `and`/`or` data with the operands' end offsets packed low word first
+    fn joined(name: &str, operands: &[Vec<u8>]) -> Vec<u8> {
+        let (mut offsets, mut data) = (U256::zero(), Vec::new());
+        for (i, operand) in operands.iter().enumerate() {
+            data.extend_from_slice(operand);
+            offsets |= U256::from(data.len()) << (32 * i);
+        }
+        router_call(name, &[Token::Uint(offsets), Token::Bytes(data)])
+    }
+
+    #[test]
+    fn and_or_split_packed_offsets() {
+        let operands = [epoch_equals(1), epoch_equals(2), epoch_equals(3)];
+        let Predicate::And(items) = Predicate::decode(&joined("and", &operands)).unwrap() else {
+            panic!("not an and");
+        };
+        assert_eq!(items.len(), 3);
+        for (item, operand) in items.iter().zip(&operands) {
+            assert_eq!(item, &Predicate::decode(operand).unwrap());
+        }
+        assert_eq!(items[1].to_string(), format!("router.epochEquals({:?}, 0, 2)", Address::repeat_byte(0xaa)));
+
+        let predicate = Predicate::decode(&joined("or", &operands[..2])).unwrap();
+        assert!(matches!(&predicate, Predicate::Or(items) if items.len() == 2));
+        // An empty `and` is all of nothing
+        assert_eq!(Predicate::decode(&joined("and", &[])).unwrap(), Predicate::And(Vec::new()));
+    }
+
+    #[test]
+    fn comparisons_and_not() {
+        let leaf = epoch_equals(7);
+        let inner = Box::new(Predicate::decode(&leaf).unwrap());
+        let value = U256::from(1_700_000_000u64);
+        for (name, expected) in [
+            ("eq", Predicate::Eq(value, inner.clone())),
+            ("lt", Predicate::Lt(value, inner.clone())),
+            ("gt", Predicate::Gt(value, inner.clone())),
+        ] {
+            let data = router_call(name, &[Token::Uint(value), Token::Bytes(leaf.clone())]);
+            assert_eq!(Predicate::decode(&data).unwrap(), expected);
+        }
+        let not = router_call("not", &[Token::Bytes(leaf.clone())]);
+        assert_eq!(Predicate::decode(&not).unwrap(), Predicate::Not(inner));
+    }
+
+    #[test]
+    fn static_call_nesting() {
+        let (token, maker) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let balance = router_call("arbitraryStaticCall", &[Token::Address(token), Token::Bytes(balance_of(maker))]);
+        let unknown = router_call("arbitraryStaticCall", &[Token::Address(token), Token::Bytes(vec![0xde, 0xad, 0xbe, 0xef])]);
+        let gt = router_call("gt", &[Token::Uint(1000.into()), Token::Bytes(balance)]);
+        let lt = router_call("lt", &[Token::Uint(5.into()), Token::Bytes(unknown)]);
+        let predicate = Predicate::decode(&joined("and", &[gt, lt])).unwrap();
+        assert_eq!(
+            predicate.to_string(),
+            format!("and({:?}.balanceOf({:?}) > 1000, {:?}.call(0xdeadbeef) < 5)", token, maker, token)
+        );
+    }
+
+    #[test]
+    fn malformed_predicates_error() {
+        let leaf = epoch_equals(1);
+        let offsets_past_data = router_call("and", &[Token::Uint(U256::from(leaf.len() + 1)), Token::Bytes(leaf.clone())]);
+        assert!(Predicate::decode(&offsets_past_data).is_err());
+        let decreasing = router_call("or", &[Token::Uint(U256::from(leaf.len()) | (U256::from(4) << 32)), Token::Bytes(leaf.clone())]);
+        assert!(Predicate::decode(&decreasing).is_err());
+        let empty_operand = router_call("and", &[Token::Uint(U256::from(leaf.len()) | (U256::from(leaf.len()) << 32)), Token::Bytes(leaf.clone())]);
+        assert!(Predicate::decode(&empty_operand).is_err());
+
+        let not = router_call("not", &[Token::Bytes(leaf)]);
+        assert!(Predicate::decode(&not[..not.len() - 40]).is_err());
+        let mut bad_offset = not.clone();
+        bad_offset[4..36].copy_from_slice(&[0xff; 32]);
+        assert!(Predicate::decode(&bad_offset).is_err());
+        assert!(Predicate::decode(&not[..3]).is_err());
+
+        // Unknown selectors are kept as raw router calls
+        assert_eq!(Predicate::decode(&[1, 2, 3, 4]).unwrap(), Predicate::RouterCall(PredicateCall::unknown(&[1, 2, 3, 4])));
+    }
+}
@@ -0,0 +1,193 @@
+use std::sync::Arc;
+use ethers::{
+    abi::{encode, Token},
+    providers::Middleware,
+    types::{Address, Bytes, Signature, H256, U256},
+    utils::keccak256,
+};
+use eyre::Result;
+
+use crate::anvil_setup::SignerClient;
+use crate::bindings::aggregation_router_v6::Order;
+use crate::bindings::erc1271::IERC1271;
+
+// EIP-712 domain of AggregationRouterV6
+pub const DOMAIN_NAME: &str = "1inch Aggregation Router";
+pub const DOMAIN_VERSION: &str = "6";
+
+const ORDER_TYPE: &str = "Order(uint256 salt,address maker,address receiver,address makerAsset,address takerAsset,uint256 makingAmount,uint256 takingAmount,uint256 makerTraits)";
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// `isValidSignature` magic value
+const ERC1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// How the order signature was accepted, or why not
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureCheck {
+    /// ECDSA signature recovers to the maker
+    Ecdsa,
+    /// Maker is a contract that accepts the signature via ERC-1271
+    Erc1271,
+    /// Neither; `recovered` is the EOA the signature actually belongs to
+    Invalid { recovered: Option<Address> },
+}
+
+impl SignatureCheck {
+    pub fn is_valid(&self) -> bool {
+        !matches!(self, SignatureCheck::Invalid { .. })
+    }
+}
+
+pub fn domain_separator(chain_id: u64, router: Address) -> H256 {
+    H256::from(keccak256(encode(&[
+        Token::FixedBytes(keccak256(DOMAIN_TYPE).to_vec()),
+        Token::FixedBytes(keccak256(DOMAIN_NAME).to_vec()),
+        Token::FixedBytes(keccak256(DOMAIN_VERSION).to_vec()),
+        Token::Uint(U256::from(chain_id)),
+        Token::Address(router),
+    ])))
+}
+
+/// `hashStruct(order)`; the packed address fields encode exactly like `address` words
+pub fn order_struct_hash(order: &Order) -> H256 {
+    H256::from(keccak256(encode(&[
+        Token::FixedBytes(keccak256(ORDER_TYPE).to_vec()),
+        Token::Uint(order.salt),
+        Token::Uint(order.maker),
+        Token::Uint(order.receiver),
+        Token::Uint(order.maker_asset),
+        Token::Uint(order.taker_asset),
+        Token::Uint(order.making_amount),
+        Token::Uint(order.taking_amount),
+        Token::Uint(order.maker_traits),
+    ])))
+}
+
+/// EIP-712 digest the maker signs, same as the router's `hashOrder`
+pub fn order_hash(order: &Order, chain_id: u64, router: Address) -> H256 {
+    let mut data = Vec::with_capacity(66);
+    data.extend_from_slice(&[0x19, 0x01]);
+    data.extend_from_slice(domain_separator(chain_id, router).as_bytes());
+    data.extend_from_slice(order_struct_hash(order).as_bytes());
+    H256::from(keccak256(data))
+}
+
+/// EIP-2098 compact signature: `vs` carries `v - 27` in its top bit and `s` below it
+pub fn signature_from_r_vs(r: [u8; 32], vs: [u8; 32]) -> Signature {
+    let vs = U256::from_big_endian(&vs);
+    Signature {
+        r: U256::from_big_endian(&r),
+        s: vs & ((U256::one() << 255) - 1),
+        v: 27 + u64::from(vs.bit(255)),
+    }
+}
+
+/// `(r, vs)` of `signature`, the inverse of `signature_from_r_vs`
+pub fn r_vs_from_signature(signature: &Signature) -> ([u8; 32], [u8; 32]) {
+    let mut vs = signature.s;
+    if signature.v == 28 || signature.v == 1 {
+        vs |= U256::one() << 255;
+    }
+    let (mut r_bytes, mut vs_bytes) = ([0u8; 32], [0u8; 32]);
+    signature.r.to_big_endian(&mut r_bytes);
+    vs.to_big_endian(&mut vs_bytes);
+    (r_bytes, vs_bytes)
+}
+
+pub fn recover_signer(hash: H256, r: [u8; 32], vs: [u8; 32]) -> Result<Address> {
+    Ok(signature_from_r_vs(r, vs).recover(hash)?)
+}
+
+/// Check the signature the way `ECDSA.recoverOrIsValidSignature` does: ECDSA recovery of
+/// `(r, vs)` first, then `isValidSignature(hash, signature)` on the maker if it has code on
+/// the fork, with the maker's raw `signature` bytes
+pub async fn verify_order_signature(
+    client: &Arc<SignerClient>,
+    maker: Address,
+    hash: H256,
+    r: [u8; 32],
+    vs: [u8; 32],
+    signature: &Bytes,
+) -> Result<SignatureCheck> {
+    let recovered = recover_signer(hash, r, vs).ok();
+    if recovered == Some(maker) {
+        return Ok(SignatureCheck::Ecdsa);
+    }
+
+    if !client.get_code(maker, None).await?.is_empty() {
+        let accepted = IERC1271::new(maker, client.clone())
+            .is_valid_signature(hash.into(), signature.clone())
+            .call()
+            .await
+            .map(|magic| magic == ERC1271_MAGIC_VALUE)
+            .unwrap_or(false);
+        if accepted {
+            return Ok(SignatureCheck::Erc1271);
+        }
+    }
+
+    Ok(SignatureCheck::Invalid { recovered })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use super::*;
+    use crate::config::simple_config::MevConfig;
+    use crate::one_inch::{AGGREGATION_ROUTER_V6, parse_contract_signature, parse_order, parse_signature, unpack_address};
+
+    fn captured() -> MevConfig {
+        serde_json::from_str(include_str!("../../mev_data_updated.json")).unwrap()
+    }
+
+    #[test]
+    fn contract_makers_keep_their_raw_signature() {
+        let mut template = captured().one_inch_orders[0].clone();
+        let raw = format!("0x{}", "ab".repeat(100));
+        template.order.signature = raw.clone();
+
+        let order_config = template.to_standard_order("0x0").unwrap();
+        assert_eq!(parse_signature(&order_config).unwrap(), ([0u8; 32], [0u8; 32]));
+        assert_eq!(parse_contract_signature(&order_config).unwrap(), Bytes::from_str(&raw).unwrap());
+    }
+
+    #[test]
+    fn captured_order_hashes() {
+        let router = Address::from_str(AGGREGATION_ROUTER_V6).unwrap();
+        let expected = [
+            "0x660d41f759823750e6b7ffb4c7519daaaf6ddcfb4dd62f90ab2823c14bf2a615",
+            "0x9af17c4d50671051929df26a608bddb11a517ac88a215796ca67ea9b2d7958d7",
+        ];
+        let config = captured();
+        assert_eq!(config.one_inch_orders.len(), expected.len());
+        for (mev_order, expected) in config.one_inch_orders.iter().zip(expected) {
+            let order = parse_order(&mev_order.to_standard_order("0x0").unwrap()).unwrap();
+            assert_eq!(order_hash(&order, 56, router), H256::from_str(expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn captured_signatures_recover_to_the_maker() {
+        let router = Address::from_str(AGGREGATION_ROUTER_V6).unwrap();
+        for mev_order in captured().one_inch_orders {
+            let order_config = mev_order.to_standard_order("0x0").unwrap();
+            let order = parse_order(&order_config).unwrap();
+            let (r, vs) = parse_signature(&order_config).unwrap();
+            let signer = recover_signer(order_hash(&order, 56, router), r, vs).unwrap();
+            assert_eq!(signer, unpack_address(order.maker));
+            assert_eq!(signer, Address::from_str(&mev_order.order.maker).unwrap());
+        }
+    }
+
+    #[test]
+    fn compact_signature_round_trip() {
+        let (r, vs) = ([0x11; 32], {
+            let mut vs = [0x22; 32];
+            vs[0] |= 0x80;
+            vs
+        });
+        let signature = signature_from_r_vs(r, vs);
+        assert_eq!(signature.v, 28);
+        assert_eq!(r_vs_from_signature(&signature), (r, vs));
+    }
+}
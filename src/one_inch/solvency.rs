@@ -0,0 +1,116 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::types::{Address, U256};
+use eyre::Result;
+use tracing::{info, warn};
+
+use crate::anvil_setup::{SignerClient, get_token_allowance, get_token_balance};
+use crate::bindings::aggregation_router_v6::Order;
+use crate::bindings::permit2::Permit2;
+use super::extension::OrderExtension;
+use super::maker_traits::MakerTraits;
+use super::{AGGREGATION_ROUTER_V6, PERMIT2, unpack_address};
+
+/// Permit2 allowance of the maker to the router for the maker asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Permit2Allowance {
+    pub amount: U256,
+    pub expiration: u64,
+}
+
+/// What the maker can actually deliver of the maker asset on the fork
+#[derive(Debug, Clone)]
+pub struct MakerSolvency {
+    pub maker: Address,
+    pub maker_asset: Address,
+    pub balance: U256,
+    /// ERC20 allowance to the router, or to Permit2 when the order transfers through it
+    pub allowance: U256,
+    /// Set when USE_PERMIT2 is on
+    pub permit2: Option<Permit2Allowance>,
+    /// The maker permit or pre-interaction can change balance or allowance during the fill,
+    /// so the cap is only a hint
+    pub can_change_during_fill: bool,
+    /// Most the router can transfer from the maker
+    pub cap: U256,
+    /// Making amount the fill needs
+    pub required: U256,
+}
+
+impl MakerSolvency {
+    pub fn is_solvent(&self) -> bool {
+        self.cap >= self.required
+    }
+
+    pub fn log_summary(&self) {
+        info!("🏦 Maker {:?} solvency for {:?}:", self.maker, self.maker_asset);
+        info!("  Balance: {} wei", self.balance);
+        info!("  Allowance: {} wei", self.allowance);
+        if let Some(permit2) = &self.permit2 {
+            info!("  Permit2 allowance: {} wei, expires {}", permit2.amount, permit2.expiration);
+        }
+        info!("  Fillable cap: {} wei, fill needs {} wei", self.cap, self.required);
+    }
+}
+
+impl fmt::Display for MakerSolvency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "maker can deliver {} of the {} needed (balance {}, allowance {}", self.cap, self.required, self.balance, self.allowance)?;
+        if let Some(permit2) = &self.permit2 {
+            write!(f, ", permit2 {}", permit2.amount)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Read the maker's balance and allowances for the maker asset and cap the fill by them
+pub async fn check_maker_solvency(
+    client: &Arc<SignerClient>,
+    order: &Order,
+    extension: &OrderExtension,
+    required: U256,
+    timestamp: u64,
+) -> Result<MakerSolvency> {
+    let traits = MakerTraits(order.maker_traits);
+    let maker = unpack_address(order.maker);
+    let maker_asset = unpack_address(order.maker_asset);
+    let router = Address::from_str(AGGREGATION_ROUTER_V6)?;
+
+    let balance = get_token_balance(client, maker_asset, maker).await?;
+    let (allowance, permit2) = if traits.use_permit2() {
+        // Router -> Permit2.transferFrom -> token.transferFrom: both allowances must cover the fill
+        let permit2_address = Address::from_str(PERMIT2)?;
+        let allowance = get_token_allowance(client, maker_asset, maker, permit2_address).await?;
+        let (amount, expiration, _nonce) = Permit2::new(permit2_address, client.clone())
+            .allowance(maker, maker_asset, router)
+            .call()
+            .await?;
+        (allowance, Some(Permit2Allowance { amount, expiration }))
+    } else {
+        (get_token_allowance(client, maker_asset, maker, router).await?, None)
+    };
+
+    let mut cap = balance.min(allowance);
+    if let Some(permit2) = &permit2 {
+        // Permit2 allowances expire at `expiration` inclusive
+        let live = if permit2.expiration < timestamp { U256::zero() } else { permit2.amount };
+        cap = cap.min(live);
+    }
+
+    let solvency = MakerSolvency {
+        maker,
+        maker_asset,
+        balance,
+        allowance,
+        permit2,
+        can_change_during_fill: !extension.maker_permit.is_empty() || traits.need_pre_interaction_call(),
+        cap,
+        required,
+    };
+    solvency.log_summary();
+    if !solvency.is_solvent() && solvency.can_change_during_fill {
+        warn!("⚠️  Maker looks insolvent, but its permit or pre-interaction may fix that during the fill");
+    }
+    Ok(solvency)
+}
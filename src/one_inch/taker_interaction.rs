@@ -0,0 +1,96 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use ethers::types::{Address, Bytes, U256};
+use eyre::Result;
+use tracing::info;
+
+use crate::anvil_setup::{SignerClient, deploy_batch_taker};
+use crate::batch_taker::{BatchCall, encode_calls};
+use crate::bindings::aggregation_router_v6::Order;
+use crate::bindings::erc20::IERC20;
+use crate::bindings::pancake_v2_router::PancakeV2Router;
+use crate::config::simple_config::TakerInteraction;
+use super::taker_traits::TakerTraits;
+use super::unpack_address;
+
+/// PancakeSwap V2 router on BSC, used by flash fills
+pub const PANCAKE_V2_ROUTER: &str = "0x10ED43C718714eb63d5aA57B78B54704E256024E";
+
+/// A taker interaction resolved for one fill
+#[derive(Debug, Clone)]
+pub struct ResolvedInteraction {
+    /// Receives the maker asset instead of the taker (TakerTraits `target`)
+    pub receiver: Option<Address>,
+    /// Contract the router calls `takerInteraction` on
+    pub target: Address,
+    pub extra_data: Bytes,
+    /// The interaction pays the taking amount, so the taker is not funded
+    pub self_funded: bool,
+}
+
+impl ResolvedInteraction {
+    /// Interaction section of `args`: `[target (20 bytes)][extraData]`
+    pub fn interaction(&self) -> Bytes {
+        let mut interaction = self.target.as_bytes().to_vec();
+        interaction.extend_from_slice(&self.extra_data);
+        interaction.into()
+    }
+
+    /// Add the receiver and interaction to `traits`
+    pub fn apply(&self, traits: TakerTraits) -> TakerTraits {
+        let traits = traits.interaction(self.interaction());
+        match self.receiver {
+            Some(receiver) => traits.target(receiver),
+            None => traits,
+        }
+    }
+}
+
+/// Turn the configured interaction into `args` bytes for a fill of `making_amount` for `taking_amount`
+pub async fn resolve_taker_interaction(
+    client: &Arc<SignerClient>,
+    interaction: &TakerInteraction,
+    order: &Order,
+    taker: Address,
+    making_amount: U256,
+    taking_amount: U256,
+) -> Result<ResolvedInteraction> {
+    match interaction {
+        TakerInteraction::PancakeV2FlashFill => pancake_v2_flash_fill(client, order, taker, making_amount, taking_amount).await,
+        TakerInteraction::Custom { target, extra_data, receiver } => Ok(ResolvedInteraction {
+            receiver: receiver.as_deref().map(Address::from_str).transpose()?,
+            target: Address::from_str(target)?,
+            extra_data: if extra_data.is_empty() { Bytes::new() } else { Bytes::from_str(extra_data)? },
+            self_funded: false,
+        }),
+    }
+}
+
+/// Have the batch taker receive the maker asset and swap all of it on PancakeSwap V2 for at
+/// least `taking_amount` of the taker asset, paid to the taker before the router pulls it
+pub async fn pancake_v2_flash_fill(
+    client: &Arc<SignerClient>,
+    order: &Order,
+    taker: Address,
+    making_amount: U256,
+    taking_amount: U256,
+) -> Result<ResolvedInteraction> {
+    let executor = deploy_batch_taker(client).await?;
+    let maker_asset = unpack_address(order.maker_asset);
+    let taker_asset = unpack_address(order.taker_asset);
+    let router = PancakeV2Router::new(Address::from_str(PANCAKE_V2_ROUTER)?, client.clone());
+
+    let approve = IERC20::new(maker_asset, client.clone()).approve(router.address(), making_amount).calldata()
+        .ok_or_else(|| eyre::eyre!("Failed to encode approve"))?;
+    let swap = router
+        .swap_exact_tokens_for_tokens(making_amount, taking_amount, vec![maker_asset, taker_asset], taker, U256::MAX)
+        .calldata()
+        .ok_or_else(|| eyre::eyre!("Failed to encode swapExactTokensForTokens"))?;
+    let calls = [
+        BatchCall { target: maker_asset, value: U256::zero(), allow_failure: false, data: approve },
+        BatchCall { target: router.address(), value: U256::zero(), allow_failure: false, data: swap },
+    ];
+
+    info!("⚡ Flash fill: {} maker asset -> at least {} taker asset on PancakeSwap V2 via {:?}", making_amount, taking_amount, executor);
+    Ok(ResolvedInteraction { receiver: Some(executor), target: executor, extra_data: encode_calls(&calls), self_funded: true })
+}
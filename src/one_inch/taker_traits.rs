@@ -0,0 +1,242 @@
+use ethers::types::{Address, Bytes, U256};
+use eyre::Result;
+
+// Flag bits, as in TakerTraitsLib (limit order protocol v4)
+const MAKER_AMOUNT_FLAG: usize = 255;
+const UNWRAP_WETH_FLAG: usize = 254;
+const SKIP_ORDER_PERMIT_FLAG: usize = 253;
+const USE_PERMIT2_FLAG: usize = 252;
+const ARGS_HAS_TARGET: usize = 251;
+
+// uint24 lengths of the extension and interaction sections of `args`
+const ARGS_EXTENSION_LENGTH_OFFSET: usize = 224;
+const ARGS_INTERACTION_LENGTH_OFFSET: usize = 200;
+const ARGS_LENGTH_BITS: usize = 24;
+
+// Low 185 bits: threshold amount
+const THRESHOLD_BITS: usize = 185;
+
+/// `takerTraits` plus the `args` bytes they describe, for `fillOrderArgs`
+///
+/// `args` is laid out as `[target (20 bytes, if set)][extension][interaction]`; the
+/// extension and interaction lengths in the traits word are taken from the actual bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TakerTraits {
+    /// `amount` is a making amount (otherwise a taking amount)
+    pub maker_amount: bool,
+    pub unwrap_weth: bool,
+    pub skip_order_permit: bool,
+    pub use_permit2: bool,
+    /// Receiver of the maker asset when it isn't the taker
+    pub target: Option<Address>,
+    pub extension: Bytes,
+    pub interaction: Bytes,
+    /// Min making amount (or max taking amount with `maker_amount`), zero for no limit
+    pub threshold: U256,
+}
+
+impl TakerTraits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn maker_amount(mut self) -> Self {
+        self.maker_amount = true;
+        self
+    }
+
+    pub fn unwrap_weth(mut self) -> Self {
+        self.unwrap_weth = true;
+        self
+    }
+
+    pub fn skip_order_permit(mut self) -> Self {
+        self.skip_order_permit = true;
+        self
+    }
+
+    pub fn use_permit2(mut self) -> Self {
+        self.use_permit2 = true;
+        self
+    }
+
+    pub fn target(mut self, target: Address) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn extension(mut self, extension: impl Into<Bytes>) -> Self {
+        self.extension = extension.into();
+        self
+    }
+
+    pub fn interaction(mut self, interaction: impl Into<Bytes>) -> Self {
+        self.interaction = interaction.into();
+        self
+    }
+
+    pub fn threshold(mut self, threshold: U256) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// The packed `takerTraits` word
+    pub fn traits(&self) -> Result<U256> {
+        let max_length = (1usize << ARGS_LENGTH_BITS) - 1;
+        if self.extension.len() > max_length || self.interaction.len() > max_length {
+            return Err(eyre::eyre!(
+                "Args section too long for takerTraits (extension {} bytes, interaction {} bytes)",
+                self.extension.len(), self.interaction.len()
+            ));
+        }
+        if self.threshold >= U256::one() << THRESHOLD_BITS {
+            return Err(eyre::eyre!("Threshold {} does not fit in {} bits", self.threshold, THRESHOLD_BITS));
+        }
+
+        let mut traits = self.threshold;
+        for (enabled, bit) in [
+            (self.maker_amount, MAKER_AMOUNT_FLAG),
+            (self.unwrap_weth, UNWRAP_WETH_FLAG),
+            (self.skip_order_permit, SKIP_ORDER_PERMIT_FLAG),
+            (self.use_permit2, USE_PERMIT2_FLAG),
+            (self.target.is_some(), ARGS_HAS_TARGET),
+        ] {
+            if enabled {
+                traits |= U256::one() << bit;
+            }
+        }
+        traits |= U256::from(self.extension.len()) << ARGS_EXTENSION_LENGTH_OFFSET;
+        traits |= U256::from(self.interaction.len()) << ARGS_INTERACTION_LENGTH_OFFSET;
+        Ok(traits)
+    }
+
+    /// The `args` bytes for `fillOrderArgs`
+    pub fn args(&self) -> Bytes {
+        let mut args = Vec::with_capacity(20 + self.extension.len() + self.interaction.len());
+        if let Some(target) = self.target {
+            args.extend_from_slice(target.as_bytes());
+        }
+        args.extend_from_slice(&self.extension);
+        args.extend_from_slice(&self.interaction);
+        args.into()
+    }
+
+    /// Traits word and args, ready for `fillOrderArgs`
+    pub fn encode(&self) -> Result<(U256, Bytes)> {
+        Ok((self.traits()?, self.args()))
+    }
+
+    /// Split a `takerTraits` word and its `args` back into their parts
+    pub fn decode(traits: U256, args: &[u8]) -> Result<Self> {
+        let length_mask = (U256::one() << ARGS_LENGTH_BITS) - 1;
+        let extension_length = ((traits >> ARGS_EXTENSION_LENGTH_OFFSET) & length_mask).as_usize();
+        let interaction_length = ((traits >> ARGS_INTERACTION_LENGTH_OFFSET) & length_mask).as_usize();
+        let has_target = traits.bit(ARGS_HAS_TARGET);
+
+        let target_length = if has_target { 20 } else { 0 };
+        let expected = target_length + extension_length + interaction_length;
+        if args.len() < expected {
+            return Err(eyre::eyre!("Args are {} bytes, takerTraits describe at least {}", args.len(), expected));
+        }
+
+        let extension_end = target_length + extension_length;
+        Ok(TakerTraits {
+            maker_amount: traits.bit(MAKER_AMOUNT_FLAG),
+            unwrap_weth: traits.bit(UNWRAP_WETH_FLAG),
+            skip_order_permit: traits.bit(SKIP_ORDER_PERMIT_FLAG),
+            use_permit2: traits.bit(USE_PERMIT2_FLAG),
+            target: has_target.then(|| Address::from_slice(&args[..20])),
+            extension: Bytes::from(args[target_length..extension_end].to_vec()),
+            interaction: Bytes::from(args[extension_end..expected].to_vec()),
+            threshold: traits & ((U256::one() << THRESHOLD_BITS) - 1),
+        })
+    }
+
+    /// Whether the `amount` of a `takerTraits` word is a making amount, without needing the args
+    pub fn is_making_amount(traits: U256) -> bool {
+        traits.bit(MAKER_AMOUNT_FLAG)
+    }
+
+    /// Whether the taker wants a WBNB maker asset unwrapped, without needing the args
+    pub fn is_unwrap_weth(traits: U256) -> bool {
+        traits.bit(UNWRAP_WETH_FLAG)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::simple_config::MevConfig;
+
+    /// Extension of the first captured BSC order in `mev_data_updated.json`
+    fn captured_extension() -> Bytes {
+        let config: MevConfig = serde_json::from_str(include_str!("../../mev_data_updated.json")).unwrap();
+        config.one_inch_orders[0].order.extension.parse().unwrap()
+    }
+
+    fn word(hex: &str) -> U256 {
+        U256::from_str_radix(hex.trim_start_matches("0x"), 16).unwrap()
+    }
+
+    fn assert_round_trip(traits: U256, args: &[u8]) -> TakerTraits {
+        let decoded = TakerTraits::decode(traits, args).unwrap();
+        let (encoded_traits, encoded_args) = decoded.encode().unwrap();
+        assert_eq!(encoded_traits, traits);
+        assert_eq!(encoded_args.as_ref(), args);
+        decoded
+    }
+
+    #[test]
+    fn captured_zero_traits() {
+        let config: MevConfig = serde_json::from_str(include_str!("../../mev_data_updated.json")).unwrap();
+        let decoded = assert_round_trip(word(&config.taker_traits), &[]);
+        assert_eq!(decoded, TakerTraits::new());
+    }
+
+    #[test]
+    fn extension_length_field() {
+        let extension = captured_extension();
+        assert_eq!(extension.len(), 0xb8);
+
+        let traits = word("0x000000b800000000000000000000000000000000000000000000000000000000");
+        let decoded = assert_round_trip(traits, &extension);
+        assert_eq!(decoded.extension, extension);
+        assert!(decoded.interaction.is_empty());
+        assert!(decoded.target.is_none());
+        assert!(!decoded.maker_amount);
+    }
+
+    #[test]
+    fn flags_target_interaction_and_threshold() {
+        let target: Address = "0x0000000000000000000000000000000000ba7c11".parse().unwrap();
+        let interaction = [target.as_bytes(), &[0x42u8; 32]].concat();
+        let mut args = target.as_bytes().to_vec();
+        args.extend_from_slice(&captured_extension());
+        args.extend_from_slice(&interaction);
+
+        // maker amount, unwrap, permit2 and target flags; extension 0xb8 and interaction 0x34 bytes; threshold 1e18
+        let traits = word("0xd80000b800003400000000000000000000000000000000000de0b6b3a7640000");
+        let decoded = assert_round_trip(traits, &args);
+        assert!(decoded.maker_amount);
+        assert!(decoded.unwrap_weth);
+        assert!(!decoded.skip_order_permit);
+        assert!(decoded.use_permit2);
+        assert_eq!(decoded.target, Some(target));
+        assert_eq!(decoded.interaction.as_ref(), interaction.as_slice());
+        assert_eq!(decoded.threshold, U256::exp10(18));
+        assert!(TakerTraits::is_making_amount(traits));
+        assert!(TakerTraits::is_unwrap_weth(traits));
+    }
+
+    #[test]
+    fn skip_order_permit_flag() {
+        let traits = word("0x2000000000000000000000000000000000000000000000000000000000000000");
+        assert!(assert_round_trip(traits, &[]).skip_order_permit);
+    }
+
+    #[test]
+    fn args_shorter_than_lengths() {
+        let traits = word("0x000000b800000000000000000000000000000000000000000000000000000000");
+        assert!(TakerTraits::decode(traits, &[0u8; 0xb7]).is_err());
+    }
+}
@@ -1,86 +1,20 @@
 use std::sync::Arc;
-use ethers::{
-    types::{Address, U256},
-    contract::Contract,
-    abi::Abi,
-};
+use std::time::Instant;
+use ethers::types::{Address, U256};
 use eyre::Result;
 use tracing::{info, warn, error};
 use std::str::FromStr;
 
-use crate::config::simple_config::SwapConfig;
+use crate::config::simple_config::{SwapConfig, ComparisonResult};
 use crate::anvil_setup::SignerClient;
+use crate::execution::{ExecutionMode, ExecutionReceipt, BalanceWatch, execute_call};
+use crate::simulation::{SimulationInputs, SimulationOutcome};
+use crate::bindings::pancake_v2_router::PancakeV2Router;
+use crate::bindings::pancake_v2_pair::PancakeV2Pair;
 
-const UNISWAP_V2_ROUTER_ABI: &str = r#"[
-    {
-        "inputs": [
-            {"internalType": "uint256", "name": "amountIn", "type": "uint256"},
-            {"internalType": "address[]", "name": "path", "type": "address[]"},
-            {"internalType": "address", "name": "to", "type": "address"},
-            {"internalType": "uint256", "name": "deadline", "type": "uint256"}
-        ],
-        "name": "swapExactETHForTokens",
-        "outputs": [{"internalType": "uint256[]", "name": "amounts", "type": "uint256[]"}],
-        "stateMutability": "payable",
-        "type": "function"
-    },
-    {
-        "inputs": [
-            {"internalType": "uint256", "name": "amountOut", "type": "uint256"},
-            {"internalType": "address[]", "name": "path", "type": "address[]"},
-            {"internalType": "address", "name": "to", "type": "address"},
-            {"internalType": "uint256", "name": "deadline", "type": "uint256"}
-        ],
-        "name": "swapETHForExactTokens",
-        "outputs": [{"internalType": "uint256[]", "name": "amounts", "type": "uint256[]"}],
-        "stateMutability": "payable",
-        "type": "function"
-    },
-    {
-        "inputs": [
-            {"internalType": "uint256", "name": "amountIn", "type": "uint256"},
-            {"internalType": "uint256", "name": "amountOutMin", "type": "uint256"},
-            {"internalType": "address[]", "name": "path", "type": "address[]"},
-            {"internalType": "address", "name": "to", "type": "address"},
-            {"internalType": "uint256", "name": "deadline", "type": "uint256"}
-        ],
-        "name": "swapExactTokensForTokens",
-        "outputs": [{"internalType": "uint256[]", "name": "amounts", "type": "uint256[]"}],
-        "stateMutability": "nonpayable",
-        "type": "function"
-    }
-]"#;
-
-const UNISWAP_V2_PAIR_ABI: &str = r#"[
-    {
-        "inputs": [],
-        "name": "getReserves",
-        "outputs": [
-            {"internalType": "uint112", "name": "_reserve0", "type": "uint112"},
-            {"internalType": "uint112", "name": "_reserve1", "type": "uint112"},
-            {"internalType": "uint32", "name": "_blockTimestampLast", "type": "uint32"}
-        ],
-        "stateMutability": "view",
-        "type": "function"
-    },
-    {
-        "inputs": [],
-        "name": "token0",
-        "outputs": [{"internalType": "address", "name": "", "type": "address"}],
-        "stateMutability": "view",
-        "type": "function"
-    },
-    {
-        "inputs": [],
-        "name": "token1",
-        "outputs": [{"internalType": "address", "name": "", "type": "address"}],
-        "stateMutability": "view",
-        "type": "function"
-    }
-]"#;
-
-pub async fn execute_swap(config: &SwapConfig, client: &Arc<SignerClient>) -> Result<()> {
-    info!("🔄 Executing swap simulation...");
+pub async fn execute_swap(config: &SwapConfig, client: &Arc<SignerClient>, mode: ExecutionMode) -> Result<SimulationOutcome> {
+    info!("🔄 Executing swap simulation ({})...", mode);
+    let started = Instant::now();
 
     // Setup router contract
     let router_contract = setup_router_contract(client, config).await?;
@@ -97,74 +31,81 @@ pub async fn execute_swap(config: &SwapConfig, client: &Arc<SignerClient>) -> Re
     let token2_addr = Address::from_str(&config.token2)?;
     let wbnb_address = Address::from_str("0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c")?;
 
+    let inputs = SimulationInputs {
+        token_in: token1_addr,
+        token_out: token2_addr,
+        amount: amount_in,
+        expected_amount_out: Some(expected_amount_out),
+    };
+    let mut outcome = SimulationOutcome::new(config.pool_type.display_name(), mode, inputs);
+
     if token1_addr == wbnb_address {
         // ETH to Token swap
-        execute_eth_to_token_swap(&router_contract, config, amount_in, expected_amount_out).await?;
+        execute_eth_to_token_swap(&router_contract, config, amount_in, expected_amount_out, mode, &mut outcome).await;
     } else if token2_addr == wbnb_address {
         // Token to ETH swap (not implemented in this example)
-        info!("⚠️  Token to ETH swap not implemented in this demo");
+        return Err(eyre::eyre!("Token to ETH swap not implemented in this demo"));
     } else {
         // Token to Token swap
-        execute_token_to_token_swap(&router_contract, config, amount_in, expected_amount_out).await?;
+        execute_token_to_token_swap(&router_contract, config, amount_in, mode, &mut outcome).await?;
     }
 
-    Ok(())
+    Ok(outcome.finish(started))
 }
 
-async fn setup_router_contract(client: &Arc<SignerClient>, config: &SwapConfig) -> Result<Contract<SignerClient>> {
-    let router_abi: Abi = serde_json::from_str(UNISWAP_V2_ROUTER_ABI)?;
+async fn setup_router_contract(client: &Arc<SignerClient>, config: &SwapConfig) -> Result<PancakeV2Router<SignerClient>> {
     let router_address = Address::from_str(config.get_router_address())?;
-    let contract = Contract::new(router_address, router_abi, client.clone());
+    let contract = PancakeV2Router::new(router_address, client.clone());
 
     Ok(contract)
 }
 
 async fn execute_eth_to_token_swap(
-    router_contract: &Contract<SignerClient>,
+    router_contract: &PancakeV2Router<SignerClient>,
     config: &SwapConfig,
     amount_in: U256,
     expected_amount_out: U256,
-) -> Result<()> {
+    mode: ExecutionMode,
+    outcome: &mut SimulationOutcome,
+) {
     info!("🔄 Executing ETH to Token swap...");
 
-    let token2_addr = Address::from_str(&config.token2)?;
-    let wbnb_address = Address::from_str("0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c")?;
-    let path = vec![wbnb_address, token2_addr];
+    let path = vec![outcome.inputs.token_in, outcome.inputs.token_out];
 
     // Try swapETHForExactTokens first (more precise)
-    match execute_swap_eth_for_exact_tokens(router_contract, &path, expected_amount_out, amount_in).await {
-        Ok(amounts) => {
+    match execute_swap_eth_for_exact_tokens(router_contract, &path, expected_amount_out, amount_in, mode).await {
+        Ok((amounts, receipt)) => {
             info!("✅ swapETHForExactTokens successful!");
             info!("  Amounts: {:?}", amounts);
-            compare_results(config, &amounts[1].to_string());
+            record_swap(config, outcome, "swapETHForExactTokens", &amounts, receipt);
         }
         Err(e) => {
             warn!("⚠️  swapETHForExactTokens failed: {}", e);
             info!("🔄 Trying swapExactETHForTokens...");
             
-            match execute_swap_exact_eth_for_tokens(router_contract, &path, amount_in).await {
-                Ok(amounts) => {
+            match execute_swap_exact_eth_for_tokens(router_contract, &path, amount_in, mode).await {
+                Ok((amounts, receipt)) => {
                     info!("✅ swapExactETHForTokens successful!");
                     info!("  Amounts: {:?}", amounts);
-                    compare_results(config, &amounts[1].to_string());
+                    record_swap(config, outcome, "swapExactETHForTokens", &amounts, receipt);
                 }
                 Err(e) => {
                     error!("❌ Both swap methods failed. Last error: {}", e);
-                    return Err(e);
+                    outcome.method = Some("swapExactETHForTokens".to_string());
+                    outcome.revert(e);
                 }
             }
         }
     }
-
-    Ok(())
 }
 
 async fn execute_swap_eth_for_exact_tokens(
-    router_contract: &Contract<SignerClient>,
+    router_contract: &PancakeV2Router<SignerClient>,
     path: &[Address],
     amount_out: U256,
     max_amount_in: U256,
-) -> Result<Vec<U256>> {
+    mode: ExecutionMode,
+) -> Result<(Vec<U256>, Option<ExecutionReceipt>)> {
     let deadline = U256::from(chrono::Utc::now().timestamp() + 300); // 5 minutes from now
     let to = router_contract.client().address();
 
@@ -176,18 +117,19 @@ async fn execute_swap_eth_for_exact_tokens(
     info!("  Deadline: {}", deadline);
 
     let call = router_contract
-        .method::<_, Vec<U256>>("swapETHForExactTokens", (amount_out, path.to_vec(), to, deadline))?
+        .swap_eth_for_exact_tokens(amount_out, path.to_vec(), to, deadline)
         .value(max_amount_in);
 
-    let result = call.call().await?;
-    Ok(result)
+    let watches = swap_balance_watches(to, Address::zero(), path[path.len() - 1]);
+    execute_call(&router_contract.client(), call, mode, &watches).await
 }
 
 async fn execute_swap_exact_eth_for_tokens(
-    router_contract: &Contract<SignerClient>,
+    router_contract: &PancakeV2Router<SignerClient>,
     path: &[Address],
     amount_in: U256,
-) -> Result<Vec<U256>> {
+    mode: ExecutionMode,
+) -> Result<(Vec<U256>, Option<ExecutionReceipt>)> {
     let deadline = U256::from(chrono::Utc::now().timestamp() + 300); // 5 minutes from now
     let to = router_contract.client().address();
     let amount_out_min = U256::zero(); // Accept any amount of tokens out
@@ -200,23 +142,23 @@ async fn execute_swap_exact_eth_for_tokens(
     info!("  Deadline: {}", deadline);
 
     let call = router_contract
-        .method::<_, Vec<U256>>("swapExactETHForTokens", (amount_out_min, path.to_vec(), to, deadline))?
+        .swap_exact_eth_for_tokens(amount_out_min, path.to_vec(), to, deadline)
         .value(amount_in);
 
-    let result = call.call().await?;
-    Ok(result)
+    let watches = swap_balance_watches(to, Address::zero(), path[path.len() - 1]);
+    execute_call(&router_contract.client(), call, mode, &watches).await
 }
 
 async fn execute_token_to_token_swap(
-    router_contract: &Contract<SignerClient>,
+    router_contract: &PancakeV2Router<SignerClient>,
     config: &SwapConfig,
     amount_in: U256,
-    _expected_amount_out: U256,
+    mode: ExecutionMode,
+    outcome: &mut SimulationOutcome,
 ) -> Result<()> {
     info!("🔄 Executing Token to Token swap...");
 
-    let token1_addr = Address::from_str(&config.token1)?;
-    let token2_addr = Address::from_str(&config.token2)?;
+    let (token1_addr, token2_addr) = (outcome.inputs.token_in, outcome.inputs.token_out);
     let path = vec![token1_addr, token2_addr];
 
     let deadline = U256::from(chrono::Utc::now().timestamp() + 300); // 5 minutes from now
@@ -230,34 +172,62 @@ async fn execute_token_to_token_swap(
     info!("  To: {}", to);
     info!("  Deadline: {}", deadline);
 
-    let result = router_contract
-        .method::<_, Vec<U256>>("swapExactTokensForTokens", (amount_in, amount_out_min, path, to, deadline))?
-        .call()
-        .await?;
-
-    info!("✅ swapExactTokensForTokens successful!");
-    info!("  Amounts: {:?}", result);
-    compare_results(config, &result[1].to_string());
+    let call = router_contract.swap_exact_tokens_for_tokens(amount_in, amount_out_min, path, to, deadline);
+    let watches = swap_balance_watches(to, token1_addr, token2_addr);
+    match execute_call(&router_contract.client(), call, mode, &watches).await {
+        Ok((result, receipt)) => {
+            info!("✅ swapExactTokensForTokens successful!");
+            info!("  Amounts: {:?}", result);
+            record_swap(config, outcome, "swapExactTokensForTokens", &result, receipt);
+        }
+        Err(e) => {
+            error!("❌ swapExactTokensForTokens failed: {}", e);
+            outcome.method = Some("swapExactTokensForTokens".to_string());
+            outcome.revert(e);
+        }
+    }
 
     Ok(())
 }
 
+/// Fill `outcome` from a router `amounts` array (first hop in, last hop out)
+fn record_swap(
+    config: &SwapConfig,
+    outcome: &mut SimulationOutcome,
+    method: &str,
+    amounts: &[U256],
+    receipt: Option<ExecutionReceipt>,
+) {
+    outcome.method = Some(method.to_string());
+    outcome.amount_in = amounts.first().copied();
+    outcome.amount_out = amounts.last().copied();
+    outcome.receipt = receipt;
+    if let Some(amount_out) = outcome.amount_out {
+        outcome.comparison = Some(compare_results(config, &amount_out.to_string()));
+    }
+}
+
+/// Balances of the swapper in the input and output token (`Address::zero()` for native BNB)
+fn swap_balance_watches(account: Address, token_in: Address, token_out: Address) -> Vec<BalanceWatch> {
+    vec![
+        BalanceWatch::new("taker token in", account, token_in),
+        BalanceWatch::new("taker token out", account, token_out),
+    ]
+}
+
 async fn check_pool_reserves(client: &Arc<SignerClient>, config: &SwapConfig) -> Result<()> {
     info!("🔍 Checking pool reserves...");
 
-    let pair_abi: Abi = serde_json::from_str(UNISWAP_V2_PAIR_ABI)?;
     let pool_address = Address::from_str(&config.pool_address)?;
-    let pair_contract = Contract::new(pool_address, pair_abi, client.clone());
+    let pair_contract = PancakeV2Pair::new(pool_address, client.clone());
 
     // Get reserves
-    let (reserve0, reserve1, _): (U256, U256, u32) = pair_contract
-        .method("getReserves", ())?
-        .call()
-        .await?;
+    let (reserve0, reserve1, _) = pair_contract.get_reserves().call().await?;
+    let (reserve0, reserve1) = (U256::from(reserve0), U256::from(reserve1));
 
     // Get token addresses
-    let token0: Address = pair_contract.method("token0", ())?.call().await?;
-    let token1: Address = pair_contract.method("token1", ())?.call().await?;
+    let token0 = pair_contract.token_0().call().await?;
+    let token1 = pair_contract.token_1().call().await?;
 
     info!("📊 Pool Reserves:");
     info!("  Token0 ({}): {} wei ({:.6} tokens)", token0, reserve0, reserve0.as_u128() as f64 / 1e18);
@@ -275,7 +245,7 @@ async fn check_pool_reserves(client: &Arc<SignerClient>, config: &SwapConfig) ->
     Ok(())
 }
 
-fn compare_results(config: &SwapConfig, actual_amount_out: &str) {
+fn compare_results(config: &SwapConfig, actual_amount_out: &str) -> ComparisonResult {
     let comparison = config.compare_result(actual_amount_out);
     
     info!("📊 Swap Result Comparison:");
@@ -291,4 +261,6 @@ fn compare_results(config: &SwapConfig, actual_amount_out: &str) {
         warn!("    • Token with transfer fees or special mechanics");
         warn!("    • Price volatility in the pool");
     }
+
+    comparison
 }
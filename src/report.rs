@@ -0,0 +1,154 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use ethers::types::U256;
+use eyre::Result;
+use serde::Serialize;
+use tracing::info;
+
+use crate::config::fork_profile::ForkBlock;
+use crate::config::simple_config::MevOneInchOrder;
+use crate::execution::ExecutionMode;
+use crate::one_inch::extension::OrderExtension;
+use crate::one_inch::predicate::Predicate;
+use crate::simulation::SimulationOutcome;
+
+/// One order or swap in a batch report; flat so it maps 1:1 onto a CSV row
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportRow {
+    pub label: String,
+    pub order_hash: Option<String>,
+    pub block: ForkBlock,
+    pub maker_asset: Option<String>,
+    pub taker_asset: Option<String>,
+    pub requested_amount: Option<String>,
+    pub making_amount: Option<String>,
+    pub taking_amount: Option<String>,
+    pub expected_amount_out: Option<String>,
+    /// Signed (actual - expected) / expected in percent
+    pub deviation_pct: Option<f64>,
+    /// "ok", "insolvent", "reverted" or "error" (simulation could not run)
+    pub status: String,
+    /// Failure cause for grouping (custom error name, `PancakeLibrary: ...`, `Panic(0x11)`, ...)
+    pub failure_cause: Option<String>,
+    pub revert_reason: Option<String>,
+    /// Decoded order predicate, e.g. `and(router.epochEquals(...), ...)`
+    pub predicate: Option<String>,
+    pub gas_used: Option<String>,
+    pub elapsed_ms: Option<u64>,
+}
+
+impl ReportRow {
+    /// Row for any executor result; for swaps `making_amount` is the amount out
+    pub fn from_outcome(label: &str, block: ForkBlock, result: &Result<SimulationOutcome>) -> Self {
+        let mut row = ReportRow {
+            label: label.to_string(),
+            order_hash: None,
+            block,
+            maker_asset: None,
+            taker_asset: None,
+            requested_amount: None,
+            making_amount: None,
+            taking_amount: None,
+            expected_amount_out: None,
+            deviation_pct: None,
+            status: "error".to_string(),
+            failure_cause: None,
+            revert_reason: None,
+            predicate: None,
+            gas_used: None,
+            elapsed_ms: None,
+        };
+
+        match result {
+            Ok(outcome) => {
+                row.order_hash = outcome.order_hash.map(|h| format!("{:?}", h));
+                row.maker_asset = Some(format!("{:?}", outcome.inputs.token_out));
+                row.taker_asset = Some(format!("{:?}", outcome.inputs.token_in));
+                row.requested_amount = Some(outcome.inputs.amount.to_string());
+                row.making_amount = outcome.amount_out.map(|a| a.to_string());
+                row.taking_amount = outcome.amount_in.map(|a| a.to_string());
+                row.expected_amount_out = outcome.inputs.expected_amount_out.map(|a| a.to_string());
+                row.deviation_pct = outcome.comparison.as_ref().and_then(|c| deviation_pct(c.expected, c.actual));
+                row.status = outcome.status().to_string();
+                row.failure_cause = outcome.error.as_ref().map(|e| e.cause());
+                row.revert_reason = outcome.revert_reason.clone();
+                row.gas_used = outcome.gas_used().map(|g| g.to_string());
+                row.elapsed_ms = Some(outcome.elapsed_ms);
+            }
+            Err(e) => {
+                row.failure_cause = Some("SetupFailed".to_string());
+                row.revert_reason = Some(e.to_string());
+            }
+        }
+        row
+    }
+
+    /// Row for a MEV order: order fields come from the input so failed runs are still identifiable,
+    /// and the deviation compares the simulated making amount with the recorded `amount_out`
+    pub fn for_mev_order(label: &str, block: ForkBlock, order: &MevOneInchOrder, result: &Result<SimulationOutcome>) -> Self {
+        let mut row = Self::from_outcome(label, block, result);
+        row.order_hash = Some(order.order.order_hash.clone());
+        row.maker_asset = Some(order.order.maker_asset.clone());
+        row.taker_asset = Some(order.order.taker_asset.clone());
+        row.predicate = OrderExtension::from_str(&order.order.extension).ok()
+            .filter(|extension| !extension.predicate.is_empty())
+            .map(|extension| match Predicate::decode(&extension.predicate) {
+                Ok(predicate) => predicate.to_string(),
+                Err(_) => format!("undecoded {}", extension.predicate),
+            });
+
+        let expected = parse_amount(&order.amount_out);
+        row.expected_amount_out = expected.map(|a| a.to_string());
+        if let (Some(expected), Ok(outcome)) = (expected, result) {
+            row.deviation_pct = outcome.amount_out.and_then(|actual| deviation_pct(to_f64(expected), to_f64(actual)));
+        }
+        row
+    }
+}
+
+/// Batch run written by `--report`
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub mode: ExecutionMode,
+    pub rows: Vec<ReportRow>,
+}
+
+impl SimulationReport {
+    pub fn new(mode: ExecutionMode, rows: Vec<ReportRow>) -> Self {
+        Self { generated_at: chrono::Utc::now(), mode, rows }
+    }
+
+    /// Write `<path>.json` and `<path>.csv` (any extension on `path` is replaced)
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json_path = path.with_extension("json");
+        fs::write(&json_path, serde_json::to_string_pretty(self)?)?;
+
+        let csv_path = path.with_extension("csv");
+        let mut writer = csv::Writer::from_path(&csv_path)?;
+        for row in &self.rows {
+            writer.serialize(row)?;
+        }
+        writer.flush()?;
+
+        info!("📝 Wrote report for {} run(s) to {} and {}", self.rows.len(), json_path.display(), csv_path.display());
+        Ok(())
+    }
+}
+
+/// Decimal or 0x-prefixed hex amount
+fn parse_amount(value: &str) -> Option<U256> {
+    match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(value).ok(),
+    }
+}
+
+fn to_f64(value: U256) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
+
+fn deviation_pct(expected: f64, actual: f64) -> Option<f64> {
+    (expected > 0.0).then(|| (actual - expected) / expected * 100.0)
+}
@@ -0,0 +1,255 @@
+use std::fmt;
+use ethers::{
+    abi::{AbiDecode, Token},
+    contract::{ContractError, EthError},
+    types::{Bytes, H256, U256},
+};
+use serde::Serialize;
+
+use crate::anvil_setup::SignerClient;
+use crate::bindings::aggregation_router_v6::{
+    AGGREGATIONROUTERV6_ABI, BadSignature, BitInvalidatedOrder, InvalidatedOrder, MakingAmountTooLow, OrderExpired,
+    PartialFillNotAllowed, PredicateIsNotTrue, PrivateOrder, RemainingInvalidatedOrder, TakingAmountExceeded,
+    TakingAmountTooHigh, TransferFromMakerToTakerFailed, TransferFromTakerToMakerFailed, WrongSeriesNonce,
+};
+
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Why a swap or fill failed, decoded from the revert data where there is any
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SimulationError {
+    // AggregationRouterV6 / limit order protocol
+    BadSignature,
+    OrderExpired,
+    PrivateOrder,
+    TakingAmountTooHigh,
+    TakingAmountExceeded,
+    MakingAmountTooLow,
+    InvalidatedOrder,
+    BitInvalidatedOrder,
+    RemainingInvalidatedOrder,
+    WrongSeriesNonce,
+    PartialFillNotAllowed,
+    PredicateIsNotTrue,
+    TransferFromMakerToTakerFailed,
+    TransferFromTakerToMakerFailed,
+    /// Pre-flight: the order fields don't hash to the order hash we were given
+    OrderHashMismatch { expected: H256, computed: H256 },
+    /// Pre-flight: the maker's balance or allowance can't cover the fill
+    MakerInsolvent { required: U256, cap: U256 },
+    /// Any other AggregationRouterV6 custom error, with its decoded arguments
+    RouterError { name: String, args: Vec<String> },
+    /// `require(..., "PancakeLibrary: ...")`, message without the prefix
+    PancakeLibrary(String),
+    /// `require(..., "PancakeRouter: ...")`, message without the prefix
+    PancakeRouter(String),
+    /// Any other `Error(string)`
+    Revert(String),
+    /// `Panic(uint256)` from a failed assert, overflow, division by zero, ...
+    Panic(u64),
+    /// Revert data with a selector we don't know
+    UnknownRevert(Bytes),
+    /// The call failed without revert data (RPC, transport, dropped or mined-and-reverted tx)
+    Other(String),
+}
+
+impl SimulationError {
+    /// Classify an executor error, decoding contract revert data when present
+    pub fn from_report(error: &eyre::Report) -> Self {
+        match error.downcast_ref::<ContractError<SignerClient>>().and_then(ContractError::as_revert) {
+            Some(data) => Self::decode(data),
+            None => SimulationError::Other(error.to_string()),
+        }
+    }
+
+    /// Decode raw revert data
+    pub fn decode(data: &[u8]) -> Self {
+        let Some(selector) = data.get(..4).map(|s| [s[0], s[1], s[2], s[3]]) else {
+            return SimulationError::UnknownRevert(Bytes::from(data.to_vec()));
+        };
+        let payload = &data[4..];
+
+        match selector {
+            ERROR_STRING_SELECTOR => match String::decode(payload) {
+                Ok(message) => Self::from_message(message),
+                Err(_) => SimulationError::UnknownRevert(Bytes::from(data.to_vec())),
+            },
+            PANIC_SELECTOR => match U256::decode(payload) {
+                Ok(code) => SimulationError::Panic(code.low_u64()),
+                Err(_) => SimulationError::UnknownRevert(Bytes::from(data.to_vec())),
+            },
+            _ => Self::decode_router_error(selector, payload)
+                .unwrap_or_else(|| SimulationError::UnknownRevert(Bytes::from(data.to_vec()))),
+        }
+    }
+
+    fn from_message(message: String) -> Self {
+        if let Some(rest) = message.strip_prefix("PancakeLibrary: ") {
+            SimulationError::PancakeLibrary(rest.to_string())
+        } else if let Some(rest) = message.strip_prefix("PancakeRouter: ") {
+            SimulationError::PancakeRouter(rest.to_string())
+        } else {
+            SimulationError::Revert(message)
+        }
+    }
+
+    fn decode_router_error(selector: [u8; 4], payload: &[u8]) -> Option<Self> {
+        let known = [
+            (BadSignature::selector(), SimulationError::BadSignature),
+            (OrderExpired::selector(), SimulationError::OrderExpired),
+            (PrivateOrder::selector(), SimulationError::PrivateOrder),
+            (TakingAmountTooHigh::selector(), SimulationError::TakingAmountTooHigh),
+            (TakingAmountExceeded::selector(), SimulationError::TakingAmountExceeded),
+            (MakingAmountTooLow::selector(), SimulationError::MakingAmountTooLow),
+            (InvalidatedOrder::selector(), SimulationError::InvalidatedOrder),
+            (BitInvalidatedOrder::selector(), SimulationError::BitInvalidatedOrder),
+            (RemainingInvalidatedOrder::selector(), SimulationError::RemainingInvalidatedOrder),
+            (WrongSeriesNonce::selector(), SimulationError::WrongSeriesNonce),
+            (PartialFillNotAllowed::selector(), SimulationError::PartialFillNotAllowed),
+            (PredicateIsNotTrue::selector(), SimulationError::PredicateIsNotTrue),
+            (TransferFromMakerToTakerFailed::selector(), SimulationError::TransferFromMakerToTakerFailed),
+            (TransferFromTakerToMakerFailed::selector(), SimulationError::TransferFromTakerToMakerFailed),
+        ];
+        if let Some((_, error)) = known.into_iter().find(|(s, _)| *s == selector) {
+            return Some(error);
+        }
+
+        let error = AGGREGATIONROUTERV6_ABI
+            .errors()
+            .find(|e| e.signature().as_bytes()[..4] == selector)?;
+        let args = error.decode(payload).ok()?.iter().map(format_token).collect();
+        Some(SimulationError::RouterError { name: error.name.clone(), args })
+    }
+
+    /// Stable name of the failure cause, for grouping reports
+    pub fn cause(&self) -> String {
+        match self {
+            SimulationError::RouterError { name, .. } => name.clone(),
+            SimulationError::OrderHashMismatch { .. } => "OrderHashMismatch".to_string(),
+            SimulationError::MakerInsolvent { .. } => "MakerInsolvent".to_string(),
+            SimulationError::PancakeLibrary(message) => format!("PancakeLibrary: {}", message),
+            SimulationError::PancakeRouter(message) => format!("PancakeRouter: {}", message),
+            SimulationError::Revert(_) => "Error(string)".to_string(),
+            SimulationError::Panic(code) => format!("Panic(0x{:02x})", code),
+            SimulationError::UnknownRevert(data) => match data.get(..4) {
+                Some(selector) => format!("0x{}", hex::encode(selector)),
+                None => "EmptyRevert".to_string(),
+            },
+            SimulationError::Other(_) => "Other".to_string(),
+            other => format!("{:?}", other),
+        }
+    }
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationError::RouterError { name, args } => write!(f, "{}({})", name, args.join(", ")),
+            SimulationError::OrderHashMismatch { expected, computed } => {
+                write!(f, "order hash mismatch: expected {:?}, computed {:?}", expected, computed)
+            }
+            SimulationError::MakerInsolvent { required, cap } => {
+                write!(f, "maker insolvent: can deliver {} of the {} needed", cap, required)
+            }
+            SimulationError::Revert(message) => write!(f, "reverted: {}", message),
+            SimulationError::Panic(code) => write!(f, "panic 0x{:02x}: {}", code, panic_description(*code)),
+            SimulationError::UnknownRevert(data) => write!(f, "reverted with unknown data {}", data),
+            SimulationError::Other(message) => write!(f, "{}", message),
+            other => write!(f, "{}", other.cause()),
+        }
+    }
+}
+
+/// Meaning of a Solidity `Panic(uint256)` code
+fn panic_description(code: u64) -> &'static str {
+    match code {
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "corrupted storage byte array",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to zero-initialized function",
+        _ => "unknown panic code",
+    }
+}
+
+/// Solidity-style rendering of a decoded argument
+pub(crate) fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(a) => format!("{:?}", a),
+        Token::Bytes(b) | Token::FixedBytes(b) => format!("0x{}", hex::encode(b)),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{encode, ParamType};
+    use ethers::types::Address;
+    use crate::bindings::aggregation_router_v6::ReturnAmountIsNotEnough;
+
+    fn revert_data(selector: [u8; 4], tokens: &[Token]) -> Vec<u8> {
+        [selector.as_slice(), &encode(tokens)].concat()
+    }
+
+    fn sample(kind: &ParamType) -> Token {
+        match kind {
+            ParamType::Address => Token::Address(Address::repeat_byte(0x42)),
+            ParamType::Uint(_) => Token::Uint(U256::from(1234)),
+            ParamType::Bool => Token::Bool(true),
+            ParamType::Bytes => Token::Bytes(vec![0xab, 0xcd]),
+            ParamType::String => Token::String("too long".to_string()),
+            other => panic!("no sample for {}", other),
+        }
+    }
+
+    #[test]
+    fn error_strings() {
+        let message = |m: &str| SimulationError::decode(&revert_data(ERROR_STRING_SELECTOR, &[Token::String(m.to_string())]));
+        assert_eq!(message("PancakeLibrary: INSUFFICIENT_LIQUIDITY"), SimulationError::PancakeLibrary("INSUFFICIENT_LIQUIDITY".to_string()));
+        assert_eq!(message("PancakeRouter: EXPIRED"), SimulationError::PancakeRouter("EXPIRED".to_string()));
+        assert_eq!(message("TransferHelper: TRANSFER_FROM_FAILED"), SimulationError::Revert("TransferHelper: TRANSFER_FROM_FAILED".to_string()));
+        assert_eq!(message("PancakeLibrary: INSUFFICIENT_INPUT_AMOUNT").cause(), "PancakeLibrary: INSUFFICIENT_INPUT_AMOUNT");
+    }
+
+    #[test]
+    fn panics() {
+        let error = SimulationError::decode(&revert_data(PANIC_SELECTOR, &[Token::Uint(U256::from(0x11))]));
+        assert_eq!(error, SimulationError::Panic(0x11));
+        assert_eq!(error.cause(), "Panic(0x11)");
+        assert_eq!(error.to_string(), "panic 0x11: arithmetic overflow or underflow");
+    }
+
+    #[test]
+    fn every_router_error_decodes_by_name() {
+        for error in AGGREGATIONROUTERV6_ABI.errors() {
+            let tokens: Vec<Token> = error.inputs.iter().map(|input| sample(&input.kind)).collect();
+            let selector = error.signature().as_bytes()[..4].try_into().unwrap();
+            let decoded = SimulationError::decode(&revert_data(selector, &tokens));
+            assert_eq!(decoded.cause(), error.name, "{:?}", decoded);
+        }
+        let decoded = SimulationError::decode(&revert_data(ReturnAmountIsNotEnough::selector(), &[Token::Uint(1.into()), Token::Uint(2.into())]));
+        assert_eq!(decoded.to_string(), "ReturnAmountIsNotEnough(1, 2)");
+        assert_eq!(SimulationError::decode(&BadSignature::selector()), SimulationError::BadSignature);
+    }
+
+    #[test]
+    fn unknown_and_short_data() {
+        let unknown = SimulationError::decode(&[0xde, 0xad, 0xbe, 0xef, 0x01]);
+        assert_eq!(unknown, SimulationError::UnknownRevert(Bytes::from(vec![0xde, 0xad, 0xbe, 0xef, 0x01])));
+        assert_eq!(unknown.cause(), "0xdeadbeef");
+        let short = SimulationError::decode(&[0x08, 0xc3]);
+        assert_eq!(short.cause(), "EmptyRevert");
+        assert_eq!(SimulationError::decode(&[]).cause(), "EmptyRevert");
+        // A known selector with a truncated payload is not decoded as that error
+        let truncated = [ERROR_STRING_SELECTOR.as_slice(), &[0u8; 16]].concat();
+        assert_eq!(SimulationError::decode(&truncated), SimulationError::UnknownRevert(Bytes::from(truncated)));
+        let truncated = [PANIC_SELECTOR.as_slice(), &[0u8; 31]].concat();
+        assert_eq!(SimulationError::decode(&truncated), SimulationError::UnknownRevert(Bytes::from(truncated)));
+    }
+}
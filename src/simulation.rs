@@ -0,0 +1,145 @@
+use std::time::Instant;
+use ethers::types::{Address, H256, U256};
+use eyre::Result;
+use serde::Serialize;
+use tracing::info;
+
+use crate::config::simple_config::ComparisonResult;
+use crate::execution::{BalanceChange, ExecutionMode, ExecutionReceipt, reverted_receipt};
+use crate::revert::SimulationError;
+
+/// What a simulation was asked to do
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationInputs {
+    pub token_in: Address,
+    pub token_out: Address,
+    /// Swap amount in, or the requested fill amount for orders
+    pub amount: U256,
+    pub expected_amount_out: Option<U256>,
+}
+
+/// Result of one swap or order fill on the fork
+///
+/// `Err` from an executor means the simulation could not be set up (RPC,
+/// funding, approvals); a call that reverts is an outcome with `revert_reason`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationOutcome {
+    pub protocol: String,
+    /// Contract method that produced the result
+    pub method: Option<String>,
+    pub mode: ExecutionMode,
+    pub inputs: SimulationInputs,
+    pub amount_in: Option<U256>,
+    pub amount_out: Option<U256>,
+    pub order_hash: Option<H256>,
+    /// Mined transaction, `Send` mode only
+    pub receipt: Option<ExecutionReceipt>,
+    /// Decoded failure of the call itself
+    pub error: Option<SimulationError>,
+    pub revert_reason: Option<String>,
+    pub comparison: Option<ComparisonResult>,
+    pub elapsed_ms: u64,
+}
+
+impl SimulationOutcome {
+    pub fn new(protocol: &str, mode: ExecutionMode, inputs: SimulationInputs) -> Self {
+        Self {
+            protocol: protocol.to_string(),
+            method: None,
+            mode,
+            inputs,
+            amount_in: None,
+            amount_out: None,
+            order_hash: None,
+            receipt: None,
+            error: None,
+            revert_reason: None,
+            comparison: None,
+            elapsed_ms: 0,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.revert_reason.is_none()
+    }
+
+    /// Rejected because the maker can't deliver, rather than a failure of the simulation itself
+    pub fn is_maker_insolvent(&self) -> bool {
+        matches!(self.error, Some(SimulationError::MakerInsolvent { .. }))
+    }
+
+    /// "ok", "insolvent" or "reverted"
+    pub fn status(&self) -> &'static str {
+        if self.is_success() {
+            "ok"
+        } else if self.is_maker_insolvent() {
+            "insolvent"
+        } else {
+            "reverted"
+        }
+    }
+
+    pub fn gas_used(&self) -> Option<U256> {
+        self.receipt.as_ref().map(|r| r.gas_used)
+    }
+
+    pub fn balance_changes(&self) -> &[BalanceChange] {
+        self.receipt.as_ref().map(|r| r.balance_changes.as_slice()).unwrap_or_default()
+    }
+
+    /// Record a revert (or any failure of the call itself), decoding the revert data
+    pub fn revert(&mut self, error: eyre::Report) {
+        if let Some(receipt) = reverted_receipt(&error) {
+            self.receipt = Some(receipt);
+        }
+        let decoded = SimulationError::from_report(&error);
+        self.revert_reason = Some(decoded.to_string());
+        self.error = Some(decoded);
+    }
+
+    /// Record pre-flight rejections; the call was never made
+    pub fn reject(&mut self, issues: Vec<SimulationError>) {
+        let reasons: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+        self.revert_reason = Some(format!("pre-flight: {}", reasons.join("; ")));
+        self.error = issues.into_iter().next();
+    }
+
+    /// Stamp the wall-clock time since `started`
+    pub fn finish(mut self, started: Instant) -> Self {
+        self.elapsed_ms = started.elapsed().as_millis() as u64;
+        self
+    }
+}
+
+/// Log one row per simulation; `labels` name each result (e.g. "MEV Order #1")
+pub fn log_summary_table(labels: &[String], results: &[Result<SimulationOutcome>]) {
+    let amount = |value: Option<U256>| value.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+
+    info!("📋 Simulation summary:");
+    info!("  {:<16} {:<14} {:<26} {:<8} {:>28} {:>28} {:>10} {:>8}",
+          "run", "protocol", "method", "status", "amount in", "amount out", "gas", "ms");
+    for (label, result) in labels.iter().zip(results) {
+        match result {
+            Ok(outcome) => {
+                let status = outcome.status();
+                info!("  {:<16} {:<14} {:<26} {:<8} {:>28} {:>28} {:>10} {:>8}",
+                      label,
+                      outcome.protocol,
+                      outcome.method.as_deref().unwrap_or("-"),
+                      status,
+                      amount(outcome.amount_in),
+                      amount(outcome.amount_out),
+                      amount(outcome.gas_used()),
+                      outcome.elapsed_ms);
+                if let Some(reason) = &outcome.revert_reason {
+                    info!("  {:<16} ↳ {}", "", reason);
+                }
+            }
+            Err(e) => info!("  {:<16} {:<14} {:<26} {:<8} {}", label, "-", "-", "error", e),
+        }
+    }
+
+    let succeeded = results.iter().filter(|r| matches!(r, Ok(o) if o.is_success())).count();
+    let insolvent = results.iter().filter(|r| matches!(r, Ok(o) if o.is_maker_insolvent())).count();
+    info!("  {} of {} simulation(s) succeeded, {} skipped for insolvent makers", succeeded, results.len(), insolvent);
+}
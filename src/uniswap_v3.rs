@@ -1,138 +1,25 @@
 use std::sync::Arc;
+use std::time::Instant;
 use ethers::{
     types::{Address, U256},
-    contract::Contract,
-    abi::Abi,
     providers::Middleware,
 };
 use eyre::Result;
-use tracing::{info, warn};
+use tracing::{info, warn, error};
 use std::str::FromStr;
 
-use crate::config::simple_config::SwapConfig;
-use crate::anvil_setup::{SignerClient, set_token_balance_anvil, approve_token, get_token_balance};
-
-// Uniswap V3 SwapRouter ABI - Key functions for swapping
-const UNISWAP_V3_ROUTER_ABI: &str = r#"[
-    {
-        "inputs": [
-            {
-                "components": [
-                    {"internalType": "address", "name": "tokenIn", "type": "address"},
-                    {"internalType": "address", "name": "tokenOut", "type": "address"},
-                    {"internalType": "uint24", "name": "fee", "type": "uint24"},
-                    {"internalType": "address", "name": "recipient", "type": "address"},
-                    {"internalType": "uint256", "name": "deadline", "type": "uint256"},
-                    {"internalType": "uint256", "name": "amountIn", "type": "uint256"},
-                    {"internalType": "uint256", "name": "amountOutMinimum", "type": "uint256"},
-                    {"internalType": "uint160", "name": "sqrtPriceLimitX96", "type": "uint160"}
-                ],
-                "internalType": "struct ISwapRouter.ExactInputSingleParams",
-                "name": "params",
-                "type": "tuple"
-            }
-        ],
-        "name": "exactInputSingle",
-        "outputs": [{"internalType": "uint256", "name": "amountOut", "type": "uint256"}],
-        "stateMutability": "payable",
-        "type": "function"
-    },
-    {
-        "inputs": [
-            {
-                "components": [
-                    {"internalType": "address", "name": "tokenIn", "type": "address"},
-                    {"internalType": "address", "name": "tokenOut", "type": "address"},
-                    {"internalType": "uint24", "name": "fee", "type": "uint24"},
-                    {"internalType": "address", "name": "recipient", "type": "address"},
-                    {"internalType": "uint256", "name": "deadline", "type": "uint256"},
-                    {"internalType": "uint256", "name": "amountOut", "type": "uint256"},
-                    {"internalType": "uint256", "name": "amountInMaximum", "type": "uint256"},
-                    {"internalType": "uint160", "name": "sqrtPriceLimitX96", "type": "uint160"}
-                ],
-                "internalType": "struct ISwapRouter.ExactOutputSingleParams",
-                "name": "params",
-                "type": "tuple"
-            }
-        ],
-        "name": "exactOutputSingle",
-        "outputs": [{"internalType": "uint256", "name": "amountIn", "type": "uint256"}],
-        "stateMutability": "payable",
-        "type": "function"
-    },
-    {
-        "inputs": [{"internalType": "uint256", "name": "deadline", "type": "uint256"}],
-        "name": "refundETH",
-        "outputs": [],
-        "stateMutability": "payable",
-        "type": "function"
-    },
-    {
-        "inputs": [
-            {"internalType": "address", "name": "token", "type": "address"},
-            {"internalType": "uint256", "name": "value", "type": "uint256"},
-            {"internalType": "uint256", "name": "deadline", "type": "uint256"},
-            {"internalType": "uint8", "name": "v", "type": "uint8"},
-            {"internalType": "bytes32", "name": "r", "type": "bytes32"},
-            {"internalType": "bytes32", "name": "s", "type": "bytes32"}
-        ],
-        "name": "selfPermit",
-        "outputs": [],
-        "stateMutability": "payable",
-        "type": "function"
-    }
-]"#;
-
-// Uniswap V3 Pool ABI - For checking pool state
-const UNISWAP_V3_POOL_ABI: &str = r#"[
-    {
-        "inputs": [],
-        "name": "slot0",
-        "outputs": [
-            {"internalType": "uint160", "name": "sqrtPriceX96", "type": "uint160"},
-            {"internalType": "int24", "name": "tick", "type": "int24"},
-            {"internalType": "uint16", "name": "observationIndex", "type": "uint16"},
-            {"internalType": "uint16", "name": "observationCardinality", "type": "uint16"},
-            {"internalType": "uint16", "name": "observationCardinalityNext", "type": "uint16"},
-            {"internalType": "uint8", "name": "feeProtocol", "type": "uint8"},
-            {"internalType": "bool", "name": "unlocked", "type": "bool"}
-        ],
-        "stateMutability": "view",
-        "type": "function"
-    },
-    {
-        "inputs": [],
-        "name": "liquidity",
-        "outputs": [{"internalType": "uint128", "name": "", "type": "uint128"}],
-        "stateMutability": "view",
-        "type": "function"
-    },
-    {
-        "inputs": [],
-        "name": "token0",
-        "outputs": [{"internalType": "address", "name": "", "type": "address"}],
-        "stateMutability": "view",
-        "type": "function"
-    },
-    {
-        "inputs": [],
-        "name": "token1",
-        "outputs": [{"internalType": "address", "name": "", "type": "address"}],
-        "stateMutability": "view",
-        "type": "function"
-    },
-    {
-        "inputs": [],
-        "name": "fee",
-        "outputs": [{"internalType": "uint24", "name": "", "type": "uint24"}],
-        "stateMutability": "view",
-        "type": "function"
-    }
-]"#;
+use crate::config::simple_config::{SwapConfig, ComparisonResult};
+use crate::anvil_setup::{SignerClient, approve_token, get_token_balance};
+use crate::funding::fund_account;
+use crate::execution::{ExecutionMode, ExecutionReceipt, BalanceWatch, execute_call};
+use crate::simulation::{SimulationInputs, SimulationOutcome};
+use crate::bindings::pancake_v3_router::{PancakeV3Router, ExactInputSingleParams};
+use crate::bindings::pancake_v3_pool::PancakeV3Pool;
 
 /// Execute a Uniswap V3 swap
-pub async fn execute_swap(config: &SwapConfig, client: &Arc<SignerClient>) -> Result<()> {
-    info!("🔄 Executing Uniswap V3 swap simulation...");
+pub async fn execute_swap(config: &SwapConfig, client: &Arc<SignerClient>, mode: ExecutionMode) -> Result<SimulationOutcome> {
+    info!("🔄 Executing Uniswap V3 swap simulation ({})...", mode);
+    let started = Instant::now();
 
     // Setup router contract
     let router_contract = setup_router_contract(client, config).await?;
@@ -157,24 +44,31 @@ pub async fn execute_swap(config: &SwapConfig, client: &Arc<SignerClient>) -> Re
     let token2_addr = Address::from_str(&config.token2)?;
     let wbnb_address = Address::from_str("0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c")?;
 
+    let inputs = SimulationInputs {
+        token_in: token1_addr,
+        token_out: token2_addr,
+        amount: amount_in,
+        expected_amount_out: Some(expected_amount_out),
+    };
+    let mut outcome = SimulationOutcome::new(config.pool_type.display_name(), mode, inputs);
+
     if token1_addr == wbnb_address {
         // ETH to Token swap
-        execute_eth_to_token_swap(&router_contract, config, amount_in, expected_amount_out).await?;
+        execute_eth_to_token_swap(&router_contract, config, amount_in, mode, &mut outcome).await?;
     } else if token2_addr == wbnb_address {
         // Token to ETH swap
         execute_token_to_eth_swap(&router_contract, config, amount_in, expected_amount_out).await?;
     } else {
         // Token to Token swap
-        execute_token_to_token_swap(&router_contract, config, amount_in, expected_amount_out).await?;
+        execute_token_to_token_swap(&router_contract, config, amount_in, mode, &mut outcome).await?;
     }
 
-    Ok(())
+    Ok(outcome.finish(started))
 }
 
-async fn setup_router_contract(client: &Arc<SignerClient>, config: &SwapConfig) -> Result<Contract<SignerClient>> {
-    let router_abi: Abi = serde_json::from_str(UNISWAP_V3_ROUTER_ABI)?;
+async fn setup_router_contract(client: &Arc<SignerClient>, config: &SwapConfig) -> Result<PancakeV3Router<SignerClient>> {
     let router_address = Address::from_str(config.get_router_address())?;
-    let contract = Contract::new(router_address, router_abi, client.clone());
+    let contract = PancakeV3Router::new(router_address, client.clone());
 
     info!("📍 Using {} Router: {}", config.pool_type.display_name(), config.get_router_address());
 
@@ -182,16 +76,17 @@ async fn setup_router_contract(client: &Arc<SignerClient>, config: &SwapConfig)
 }
 
 async fn execute_eth_to_token_swap(
-    router_contract: &Contract<SignerClient>,
+    router_contract: &PancakeV3Router<SignerClient>,
     config: &SwapConfig,
     amount_in: U256,
-    expected_amount_out: U256,
+    mode: ExecutionMode,
+    outcome: &mut SimulationOutcome,
 ) -> Result<()> {
     info!("🔄 Executing V3 ETH to Token swap...");
 
     let token_in = Address::from_str(&config.token1)?;
     let token_out = Address::from_str(&config.token2)?;
-    let fee = config.fee as u32; // Fee tier (500, 3000, 10000)
+    let fee = config.fee; // Fee tier (500, 3000, 10000)
     let recipient = router_contract.client().address();
     let deadline = U256::from(chrono::Utc::now().timestamp() + 300); // 5 minutes from now
     let amount_out_minimum = U256::zero(); // Accept any amount of tokens out
@@ -207,7 +102,7 @@ async fn execute_eth_to_token_swap(
     info!("  Deadline: {}", deadline);
 
     // Create ExactInputSingleParams struct
-    let params = (
+    let params = ExactInputSingleParams {
         token_in,
         token_out,
         fee,
@@ -216,44 +111,44 @@ async fn execute_eth_to_token_swap(
         amount_in,
         amount_out_minimum,
         sqrt_price_limit_x96,
-    );
+    };
 
     let call = router_contract
-        .method::<_, U256>("exactInputSingle", (params,))?
+        .exact_input_single(params)
         .value(amount_in);
 
-    let result = call.call().await?;
-    
-    info!("✅ exactInputSingle successful!");
-    info!("  Amount Out: {} wei ({:.6} tokens)", result, result.as_u128() as f64 / 1e18);
-    
-    compare_results(config, &result.to_string());
+    let watches = vec![
+        BalanceWatch::new("taker token in", recipient, Address::zero()),
+        BalanceWatch::new("taker token out", recipient, token_out),
+    ];
+    let result = execute_call(&router_contract.client(), call, mode, &watches).await;
+    record_swap(config, outcome, amount_in, result);
 
     Ok(())
 }
 
 async fn execute_token_to_eth_swap(
-    _router_contract: &Contract<SignerClient>,
+    _router_contract: &PancakeV3Router<SignerClient>,
     _config: &SwapConfig,
     _amount_in: U256,
     _expected_amount_out: U256,
 ) -> Result<()> {
     info!("🔄 Executing V3 Token to ETH swap...");
-    warn!("⚠️  Token to ETH swap not implemented in this demo");
-    Ok(())
+    Err(eyre::eyre!("Token to ETH swap not implemented in this demo"))
 }
 
 async fn execute_token_to_token_swap(
-    router_contract: &Contract<SignerClient>,
+    router_contract: &PancakeV3Router<SignerClient>,
     config: &SwapConfig,
     amount_in: U256,
-    _expected_amount_out: U256,
+    mode: ExecutionMode,
+    outcome: &mut SimulationOutcome,
 ) -> Result<()> {
     info!("🔄 Executing V3 Token to Token swap...");
 
     let token_in = Address::from_str(&config.token1)?;
     let token_out = Address::from_str(&config.token2)?;
-    let fee = config.fee as u32; // Fee tier
+    let fee = config.fee; // Fee tier
     let recipient = router_contract.client().address();
     let deadline = U256::from(chrono::Utc::now().timestamp() + 300);
     let amount_out_minimum = U256::zero();
@@ -265,7 +160,7 @@ async fn execute_token_to_token_swap(
     info!("  Fee: {}", fee);
     info!("  Amount In: {} wei", amount_in);
 
-    let params = (
+    let params = ExactInputSingleParams {
         token_in,
         token_out,
         fee,
@@ -274,21 +169,45 @@ async fn execute_token_to_token_swap(
         amount_in,
         amount_out_minimum,
         sqrt_price_limit_x96,
-    );
+    };
 
-    let call = router_contract
-        .method::<_, U256>("exactInputSingle", (params,))?;
+    let call = router_contract.exact_input_single(params);
 
-    let result = call.call().await?;
-    
-    info!("✅ exactInputSingle successful!");
-    info!("  Amount Out: {} wei ({:.6} tokens)", result, result.as_u128() as f64 / 1e18);
-    
-    compare_results(config, &result.to_string());
+    let watches = vec![
+        BalanceWatch::new("taker token in", recipient, token_in),
+        BalanceWatch::new("taker token out", recipient, token_out),
+    ];
+    let result = execute_call(&router_contract.client(), call, mode, &watches).await;
+    record_swap(config, outcome, amount_in, result);
 
     Ok(())
 }
 
+/// Fill `outcome` from an `exactInputSingle` result
+fn record_swap(
+    config: &SwapConfig,
+    outcome: &mut SimulationOutcome,
+    amount_in: U256,
+    result: Result<(U256, Option<ExecutionReceipt>)>,
+) {
+    outcome.method = Some("exactInputSingle".to_string());
+    match result {
+        Ok((amount_out, receipt)) => {
+            info!("✅ exactInputSingle successful!");
+            info!("  Amount Out: {} wei ({:.6} tokens)", amount_out, amount_out.as_u128() as f64 / 1e18);
+
+            outcome.amount_in = Some(amount_in);
+            outcome.amount_out = Some(amount_out);
+            outcome.receipt = receipt;
+            outcome.comparison = Some(compare_results(config, &amount_out.to_string()));
+        }
+        Err(e) => {
+            error!("❌ exactInputSingle failed: {}", e);
+            outcome.revert(e);
+        }
+    }
+}
+
 async fn prepare_tokens_for_swap(client: &Arc<SignerClient>, config: &SwapConfig) -> Result<()> {
     info!("🔧 Preparing tokens for V3 swap...");
 
@@ -319,7 +238,8 @@ async fn prepare_tokens_for_swap(client: &Arc<SignerClient>, config: &SwapConfig
             info!("⚠️  Insufficient token balance. Setting token balance...");
             let required_amount = amount_in * 2; // Get 2x what we need for safety
 
-            match set_token_balance_anvil(client, token_in, account, required_amount).await {
+            let funding = config.funding.clone().unwrap_or_default();
+            match fund_account(client, token_in, account, required_amount, &funding).await {
                 Ok(_) => info!("✅ Successfully set token balance"),
                 Err(e) => {
                     warn!("⚠️  Failed to set token balance: {}", e);
@@ -346,26 +266,22 @@ async fn prepare_tokens_for_swap(client: &Arc<SignerClient>, config: &SwapConfig
 async fn check_pool_state(client: &Arc<SignerClient>, config: &SwapConfig) -> Result<()> {
     info!("🔍 Checking V3 pool state...");
 
-    let pool_abi: Abi = serde_json::from_str(UNISWAP_V3_POOL_ABI)?;
     let pool_address = Address::from_str(&config.pool_address)?;
-    let pool_contract = Contract::new(pool_address, pool_abi, client.clone());
+    let pool_contract = PancakeV3Pool::new(pool_address, client.clone());
 
     // Get slot0 (current price and tick)
-    let (sqrt_price_x96, tick, _obs_index, _obs_cardinality, _obs_cardinality_next, _fee_protocol, unlocked):
-        (U256, i32, u16, u16, u16, u8, bool) = pool_contract
-        .method("slot0", ())?
-        .call()
-        .await?;
+    let (sqrt_price_x96, tick, _obs_index, _obs_cardinality, _obs_cardinality_next, _fee_protocol, unlocked) =
+        pool_contract.slot_0().call().await?;
 
     // Get liquidity
-    let liquidity: u128 = pool_contract.method("liquidity", ())?.call().await?;
+    let liquidity = pool_contract.liquidity().call().await?;
 
     // Get token addresses
-    let token0: Address = pool_contract.method("token0", ())?.call().await?;
-    let token1: Address = pool_contract.method("token1", ())?.call().await?;
+    let token0 = pool_contract.token_0().call().await?;
+    let token1 = pool_contract.token_1().call().await?;
 
     // Get fee tier
-    let fee_tier: u32 = pool_contract.method("fee", ())?.call().await?;
+    let fee_tier = pool_contract.fee().call().await?;
 
     info!("📊 V3 Pool State:");
     info!("  Pool Address: {}", pool_address);
@@ -395,7 +311,7 @@ async fn check_pool_state(client: &Arc<SignerClient>, config: &SwapConfig) -> Re
     Ok(())
 }
 
-fn compare_results(config: &SwapConfig, actual_amount_out: &str) {
+fn compare_results(config: &SwapConfig, actual_amount_out: &str) -> ComparisonResult {
     let comparison = config.compare_result(actual_amount_out);
 
     info!("📊 V3 Swap Result Comparison:");
@@ -412,4 +328,6 @@ fn compare_results(config: &SwapConfig, actual_amount_out: &str) {
         warn!("    • Price impact from large trades");
         warn!("    • Different fee calculations in V3");
     }
+
+    comparison
 }